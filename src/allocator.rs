@@ -0,0 +1,221 @@
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::ptr::{self, NonNull};
+
+/// Error returned by an [`Allocator`] when it cannot satisfy a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError;
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("memory allocation failed")
+    }
+}
+
+impl std::error::Error for AllocError {}
+
+/// Memory source used by `RawVec` (and therefore `Vec`) for every allocation.
+///
+/// `RawVec` never asks for zero-sized layouts: empty buffers and zero-sized
+/// element types use a dangling pointer and are never passed to the allocator.
+///
+/// # Safety
+///
+/// Implementations must return blocks that fit the requested layout and stay
+/// valid until passed back to `deallocate`, `grow` or `shrink` on the same
+/// allocator (or a clone of it).
+pub unsafe trait Allocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError>;
+
+    /// # Safety
+    ///
+    /// `ptr` must have been allocated by this allocator with `layout`.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// # Safety
+    ///
+    /// `ptr` must have been allocated by this allocator with `old_layout`, and
+    /// `new_layout.size()` must be at least `old_layout.size()`.
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        debug_assert!(new_layout.size() >= old_layout.size());
+
+        // Fallback: allocate + copy + deallocate
+        let new_ptr = self.allocate(new_layout)?;
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), old_layout.size());
+        self.deallocate(ptr, old_layout);
+        Ok(new_ptr)
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` must have been allocated by this allocator with `old_layout`, and
+    /// `new_layout.size()` must be at most `old_layout.size()`.
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        debug_assert!(new_layout.size() <= old_layout.size());
+
+        let new_ptr = self.allocate(new_layout)?;
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), new_layout.size());
+        self.deallocate(ptr, old_layout);
+        Ok(new_ptr)
+    }
+}
+
+/// The global allocator, backed by `std::alloc`. This is the default for `Vec`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Global;
+
+unsafe impl Allocator for Global {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        debug_assert!(layout.size() != 0, "allocate: zero-sized layout");
+        NonNull::new(unsafe { alloc::alloc(layout) }).ok_or(AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        alloc::dealloc(ptr.as_ptr(), layout);
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        debug_assert!(new_layout.size() >= old_layout.size());
+        debug_assert_eq!(old_layout.align(), new_layout.align());
+
+        // realloc may extend in place, avoiding the copy
+        NonNull::new(alloc::realloc(ptr.as_ptr(), old_layout, new_layout.size()))
+            .ok_or(AllocError)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        debug_assert!(new_layout.size() <= old_layout.size());
+        debug_assert_eq!(old_layout.align(), new_layout.align());
+
+        NonNull::new(alloc::realloc(ptr.as_ptr(), old_layout, new_layout.size()))
+            .ok_or(AllocError)
+    }
+}
+
+// Lets a vector borrow a longer-lived allocator (arenas, pools)
+unsafe impl<A: Allocator + ?Sized> Allocator for &A {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        (**self).allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        (**self).deallocate(ptr, layout)
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        (**self).grow(ptr, old_layout, new_layout)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        (**self).shrink(ptr, old_layout, new_layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    // Only implements the required methods, so grow/shrink use the defaults
+    struct Counting {
+        live: Cell<usize>,
+    }
+
+    unsafe impl Allocator for Counting {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+            self.live.set(self.live.get() + 1);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.live.set(self.live.get() - 1);
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    #[test]
+    fn test_global_allocate_deallocate() {
+        let layout = Layout::array::<u64>(4).unwrap();
+        let ptr = Global.allocate(layout).unwrap();
+        unsafe {
+            ptr.as_ptr().cast::<u64>().write(42);
+            assert_eq!(ptr.as_ptr().cast::<u64>().read(), 42);
+            Global.deallocate(ptr, layout);
+        }
+    }
+
+    #[test]
+    fn test_global_grow_preserves_contents() {
+        let old_layout = Layout::array::<u32>(2).unwrap();
+        let new_layout = Layout::array::<u32>(16).unwrap();
+        unsafe {
+            let ptr = Global.allocate(old_layout).unwrap();
+            ptr.as_ptr().cast::<u32>().write(7);
+            ptr.as_ptr().cast::<u32>().add(1).write(9);
+            let grown = Global.grow(ptr, old_layout, new_layout).unwrap();
+            assert_eq!(grown.as_ptr().cast::<u32>().read(), 7);
+            assert_eq!(grown.as_ptr().cast::<u32>().add(1).read(), 9);
+            Global.deallocate(grown, new_layout);
+        }
+    }
+
+    #[test]
+    fn test_default_grow_and_shrink() {
+        let alloc = Counting { live: Cell::new(0) };
+        let small = Layout::array::<u16>(2).unwrap();
+        let large = Layout::array::<u16>(8).unwrap();
+        unsafe {
+            let ptr = alloc.allocate(small).unwrap();
+            ptr.as_ptr().cast::<u16>().write(3);
+            let ptr = alloc.grow(ptr, small, large).unwrap();
+            assert_eq!(alloc.live.get(), 1);
+            assert_eq!(ptr.as_ptr().cast::<u16>().read(), 3);
+            let ptr = alloc.shrink(ptr, large, small).unwrap();
+            assert_eq!(alloc.live.get(), 1);
+            assert_eq!(ptr.as_ptr().cast::<u16>().read(), 3);
+            alloc.deallocate(ptr, small);
+        }
+        assert_eq!(alloc.live.get(), 0);
+    }
+
+    #[test]
+    fn test_allocator_by_reference() {
+        let alloc = Counting { live: Cell::new(0) };
+        let by_ref = &alloc;
+        let layout = Layout::new::<u64>();
+        let ptr = by_ref.allocate(layout).unwrap();
+        assert_eq!(alloc.live.get(), 1);
+        unsafe { by_ref.deallocate(ptr, layout) };
+        assert_eq!(alloc.live.get(), 0);
+    }
+}
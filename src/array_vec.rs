@@ -0,0 +1,625 @@
+use crate::buffer::Buffer;
+use crate::error::{CapacityError, GetManyMutError};
+use crate::index::{self, VecIndex};
+use crate::iter::{self, Iter, IterMut};
+use crate::range::slice_range;
+use crate::Vec;
+use std::hash::{Hash, Hasher};
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ops::{Deref, DerefMut, Index, IndexMut, RangeBounds};
+use std::slice::SliceIndex;
+use std::{cmp, ptr, slice};
+
+/// A vector with a fixed capacity of `N` elements stored inline. Never
+/// allocates: operations that would exceed `N` return a `CapacityError`.
+pub struct ArrayVec<T, const N: usize> {
+    data: MaybeUninit<[T; N]>,
+    len: usize,
+}
+
+impl<T, const N: usize> ArrayVec<T, N> {
+    pub const fn new() -> Self {
+        ArrayVec {
+            data: MaybeUninit::uninit(),
+            len: 0,
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub fn remaining_capacity(&self) -> usize {
+        N - self.len
+    }
+
+    fn as_ptr(&self) -> *const T {
+        self.data.as_ptr() as *const T
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut T {
+        self.data.as_mut_ptr() as *mut T
+    }
+
+    /// Appends `item`, handing it back if the vector is full.
+    pub fn push(&mut self, item: T) -> Result<(), CapacityError<T>> {
+        if self.len == N {
+            return Err(CapacityError::new(item));
+        }
+        unsafe {
+            ptr::write(self.as_mut_ptr().add(self.len), item);
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            Some(unsafe { ptr::read(self.as_ptr().add(self.len)) })
+        }
+    }
+
+    /// Inserts `item` at `index`, handing it back if the vector is full.
+    /// Panics if `index > len`.
+    pub fn try_insert(&mut self, index: usize, item: T) -> Result<(), CapacityError<T>> {
+        if index > self.len {
+            panic!(
+                "insertion index (is {}) should be <= len (is {})",
+                index, self.len
+            );
+        }
+        if self.len == N {
+            return Err(CapacityError::new(item));
+        }
+
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            ptr::copy(p, p.add(1), self.len - index);
+            ptr::write(p, item);
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        if index >= self.len {
+            panic!(
+                "removal index (is {}) should be < len (is {})",
+                index, self.len
+            );
+        }
+
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            let item = ptr::read(p);
+            ptr::copy(p.add(1), p, self.len - index - 1);
+            self.len -= 1;
+            item
+        }
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+
+        // Shorten first so a panicking destructor can't cause a double drop
+        let tail = ptr::slice_from_raw_parts_mut(
+            unsafe { self.as_mut_ptr().add(len) },
+            self.len - len,
+        );
+        self.len = len;
+        unsafe {
+            ptr::drop_in_place(tail);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::from_raw(self.as_ptr(), 0, self.len)
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut::from_raw(self.as_mut_ptr(), 0, self.len)
+    }
+
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> iter::Drain<&mut Self> {
+        let range = slice_range(range, self.len);
+        iter::Drain::from_buffer(self, range.start, range.end)
+    }
+
+    /// Keeps only the elements for which `f` returns true, in order.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.retain_mut(|item| f(item));
+    }
+
+    /// Like `retain`, but the predicate may mutate the elements it keeps.
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
+        self.extract_if(.., |item| !f(item)).for_each(drop);
+    }
+
+    /// Removes and yields the elements in `range` for which `filter` returns
+    /// true. Elements not yet visited when the iterator is dropped are kept.
+    pub fn extract_if<F, R>(&mut self, range: R, filter: F) -> iter::ExtractIf<&mut Self, F>
+    where
+        F: FnMut(&mut T) -> bool,
+        R: RangeBounds<usize>,
+    {
+        let range = slice_range(range, self.len);
+        iter::ExtractIf::from_buffer(self, range.start, range.end, filter)
+    }
+
+    /// Removes consecutive elements that map to the same key.
+    pub fn dedup_by_key<F, K>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut T) -> K,
+        K: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    /// Removes consecutive elements for which `same_bucket(current, previous)`
+    /// returns true, keeping the first of each run.
+    pub fn dedup_by<F>(&mut self, same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        self.dedup_in_place(same_bucket);
+    }
+
+    /// Returns the element or subslice at `index`, or `None` if out of bounds.
+    pub fn get<I: SliceIndex<[T]>>(&self, index: I) -> Option<&I::Output> {
+        self.as_slice().get(index)
+    }
+
+    pub fn get_mut<I: SliceIndex<[T]>>(&mut self, index: I) -> Option<&mut I::Output> {
+        self.as_mut_slice().get_mut(index)
+    }
+
+    /// Returns mutable references to several distinct elements at once.
+    /// Fails if any index is out of bounds or two indices are equal.
+    pub fn get_many_mut<const M: usize>(
+        &mut self,
+        indices: [usize; M],
+    ) -> Result<[&mut T; M], GetManyMutError> {
+        index::get_many_mut(self.as_mut_slice(), indices)
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        self.deref()
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.deref_mut()
+    }
+
+    /// Copies the elements into a heap-allocated `Vec`.
+    pub fn into_vec(self) -> Vec<T> {
+        if self.len == 0 {
+            return Vec::new();
+        }
+        let this = ManuallyDrop::new(self);
+        let mut vec = Vec::with_capacity(this.len);
+        unsafe {
+            ptr::copy_nonoverlapping(this.as_ptr(), vec.buf.ptr(), this.len);
+        }
+        vec.len = this.len;
+        vec
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayVec<T, N> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.as_mut_ptr(), self.len));
+        }
+    }
+}
+
+impl<T, const N: usize> Deref for ArrayVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+}
+
+impl<T, const N: usize> DerefMut for ArrayVec<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+}
+
+impl<T, I: VecIndex<T>, const N: usize> Index<I> for ArrayVec<T, N> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        match self.as_slice().get(index.clone()) {
+            Some(output) => output,
+            None => index.out_of_bounds(self.len),
+        }
+    }
+}
+
+impl<T, I: VecIndex<T>, const N: usize> IndexMut<I> for ArrayVec<T, N> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        let len = self.len;
+        match self.as_mut_slice().get_mut(index.clone()) {
+            Some(output) => output,
+            None => index.out_of_bounds(len),
+        }
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayVec<T, N> {
+    fn clone(&self) -> Self {
+        let mut new_vec = ArrayVec::new();
+        for item in self.iter() {
+            // Same length as self, so this can't exceed N
+            let _ = new_vec.push(item.clone());
+        }
+        new_vec
+    }
+}
+
+impl<T: Clone, const N: usize> ArrayVec<T, N> {
+    /// Appends clones of every element in `other`. Like `extend`, panics if
+    /// they don't fit.
+    pub fn extend_from_slice(&mut self, other: &[T]) {
+        if other.len() > self.remaining_capacity() {
+            panic!("extend_from_slice: capacity (is {}) exceeded", N);
+        }
+        self.extend_cloned(other);
+    }
+}
+
+impl<T: PartialEq, const N: usize> ArrayVec<T, N> {
+    /// Removes consecutive repeated elements.
+    pub fn dedup(&mut self) {
+        self.dedup_by(|a, b| a == b);
+    }
+}
+
+// Element-wise equality and ordering, like slices; the capacities of the two
+// sides don't have to match
+impl<T: PartialEq<U>, U, const N: usize, const M: usize> PartialEq<ArrayVec<U, M>> for ArrayVec<T, N> {
+    fn eq(&self, other: &ArrayVec<U, M>) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: PartialEq<U>, U, const N: usize> PartialEq<[U]> for ArrayVec<T, N> {
+    fn eq(&self, other: &[U]) -> bool {
+        self[..] == *other
+    }
+}
+
+impl<T: PartialEq<U>, U, const N: usize> PartialEq<&[U]> for ArrayVec<T, N> {
+    fn eq(&self, other: &&[U]) -> bool {
+        self[..] == **other
+    }
+}
+
+impl<T: PartialEq<U>, U, const N: usize, const M: usize> PartialEq<[U; M]> for ArrayVec<T, N> {
+    fn eq(&self, other: &[U; M]) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for ArrayVec<T, N> {}
+
+impl<T: PartialOrd, const N: usize> PartialOrd for ArrayVec<T, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        PartialOrd::partial_cmp(&**self, &**other)
+    }
+}
+
+impl<T: Ord, const N: usize> Ord for ArrayVec<T, N> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        Ord::cmp(&**self, &**other)
+    }
+}
+
+// Hashes exactly like the equivalent slice
+impl<T: Hash, const N: usize> Hash for ArrayVec<T, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+impl<T: std::fmt::Debug, const N: usize> std::fmt::Debug for ArrayVec<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const N: usize> Default for ArrayVec<T, N> {
+    fn default() -> Self {
+        ArrayVec::new()
+    }
+}
+
+// Extending past N is a bug in the caller, like indexing out of bounds
+impl<T, const N: usize> Extend<T> for ArrayVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            if self.push(item).is_err() {
+                panic!("extend: capacity (is {}) exceeded", N);
+            }
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for ArrayVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = ArrayVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T, const N: usize> From<ArrayVec<T, N>> for Vec<T> {
+    fn from(array: ArrayVec<T, N>) -> Self {
+        array.into_vec()
+    }
+}
+
+impl<T, const N: usize> TryFrom<Vec<T>> for ArrayVec<T, N> {
+    type Error = CapacityError<Vec<T>>;
+
+    /// Moves the elements out of `vec`, or returns it untouched if it holds
+    /// more than `N` elements.
+    fn try_from(mut vec: Vec<T>) -> Result<Self, Self::Error> {
+        if vec.len > N {
+            return Err(CapacityError::new(vec));
+        }
+
+        let mut array = ArrayVec::new();
+        unsafe {
+            ptr::copy_nonoverlapping(vec.buf.ptr(), array.as_mut_ptr(), vec.len);
+        }
+        array.len = vec.len;
+        // The elements now belong to the ArrayVec
+        vec.len = 0;
+        Ok(array)
+    }
+}
+
+impl<T, const N: usize> IntoIterator for ArrayVec<T, N> {
+    type Item = T;
+    type IntoIter = iter::IntoIter<ArrayVec<T, N>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        iter::IntoIter::from_buffer(self)
+    }
+}
+
+unsafe impl<T, const N: usize> Buffer for ArrayVec<T, N> {
+    type Item = T;
+
+    fn as_ptr(&self) -> *const T {
+        self.as_ptr()
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut T {
+        self.as_mut_ptr()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    unsafe fn set_len(&mut self, len: usize) {
+        self.len = len;
+    }
+
+    // The inline array can't grow
+    fn reserve_total(&mut self, total: usize) {
+        assert!(total <= N, "capacity (is {}) exceeded", N);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_push_until_full() {
+        let mut vec: ArrayVec<i32, 3> = ArrayVec::new();
+        assert!(vec.push(1).is_ok());
+        assert!(vec.push(2).is_ok());
+        assert!(vec.push(3).is_ok());
+        assert!(vec.is_full());
+
+        let err = vec.push(4).unwrap_err();
+        assert_eq!(err.into_inner(), 4);
+        assert_eq!(vec.as_slice(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_try_insert_and_remove() {
+        let mut vec: ArrayVec<i32, 3> = ArrayVec::new();
+        vec.try_insert(0, 3).unwrap();
+        vec.try_insert(0, 1).unwrap();
+        vec.try_insert(1, 2).unwrap();
+        assert_eq!(vec.as_slice(), &[1, 2, 3]);
+        assert_eq!(*vec.try_insert(1, 9).unwrap_err().element(), 9);
+
+        assert_eq!(vec.remove(1), 2);
+        assert_eq!(vec.as_slice(), &[1, 3]);
+        assert_eq!(vec.remaining_capacity(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion index (is 2) should be <= len (is 0)")]
+    fn test_try_insert_out_of_bounds() {
+        let mut vec: ArrayVec<i32, 3> = ArrayVec::new();
+        let _ = vec.try_insert(2, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "removal index (is 0) should be < len (is 0)")]
+    fn test_remove_out_of_bounds() {
+        let mut vec: ArrayVec<i32, 3> = ArrayVec::new();
+        vec.remove(0);
+    }
+
+    #[test]
+    fn test_truncate_and_drain() {
+        let mut vec: ArrayVec<i32, 8> = (0..8).collect();
+        vec.truncate(6);
+        let drained: std::vec::Vec<_> = vec.drain(1..4).collect();
+        assert_eq!(drained, [1, 2, 3]);
+        assert_eq!(vec.as_slice(), &[0, 4, 5]);
+    }
+
+    #[test]
+    fn test_deref_to_slice() {
+        let mut vec: ArrayVec<i32, 4> = (0..4).collect();
+        vec.reverse();
+        assert_eq!(vec.first(), Some(&3));
+        assert!(vec.contains(&0));
+    }
+
+    #[test]
+    fn test_vec_round_trip() {
+        let array: ArrayVec<String, 4> = ["a", "b"].iter().map(|s| s.to_string()).collect();
+        let vec: Vec<String> = array.into();
+        assert_eq!(vec.len(), 2);
+
+        let array = ArrayVec::<String, 4>::try_from(vec).unwrap();
+        assert_eq!(array.as_slice(), &["a", "b"]);
+
+        let mut big = Vec::new();
+        big.extend(0..5);
+        let err = ArrayVec::<i32, 4>::try_from(big).unwrap_err();
+        assert_eq!(err.into_inner().len(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "extend: capacity (is 2) exceeded")]
+    fn test_extend_past_capacity() {
+        let _: ArrayVec<i32, 2> = (0..3).collect();
+    }
+
+    #[test]
+    #[should_panic(expected = "extend_from_slice: capacity (is 4) exceeded")]
+    fn test_extend_from_slice_past_capacity() {
+        let mut vec: ArrayVec<i32, 4> = (0..2).collect();
+        vec.extend_from_slice(&[2, 3, 4]);
+    }
+
+    #[test]
+    fn test_vec_methods() {
+        let mut vec: ArrayVec<i32, 16> = ArrayVec::new();
+        vec.extend_from_slice(&[1, 1, 2, 3, 3, 3, 4, 5, 6]);
+        vec.dedup();
+        assert_eq!(vec, [1, 2, 3, 4, 5, 6]);
+        vec.retain(|&x| x % 2 == 0);
+        assert_eq!(vec, [2, 4, 6]);
+
+        assert_eq!(&vec[1..], &[4, 6]);
+        vec[..2].reverse();
+        assert_eq!(vec, [4, 2, 6]);
+        assert_eq!(vec.get(1..), Some(&[2, 6][..]));
+        assert_eq!(vec.get(3), None);
+
+        let [a, b] = vec.get_many_mut([0, 2]).unwrap();
+        std::mem::swap(a, b);
+        assert_eq!(vec, [6, 2, 4]);
+        assert!(vec.get_many_mut([1, 1]).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index (is 5) should be <= len (is 3)")]
+    fn test_range_index_out_of_bounds() {
+        let vec: ArrayVec<i32, 8> = (0..3).collect();
+        let _ = &vec[..5];
+    }
+
+    #[test]
+    fn test_eq_ord_hash() {
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash<T: Hash + ?Sized>(value: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let a: ArrayVec<i32, 4> = (0..3).collect();
+        let b: ArrayVec<i32, 8> = (0..3).collect();
+        assert_eq!(a, b);
+        assert_eq!(a, &[0, 1, 2][..]);
+        assert_eq!(hash(&a), hash(&[0, 1, 2][..]));
+
+        let c: ArrayVec<i32, 4> = (1..3).collect();
+        assert!(a < c);
+        assert_eq!(a.cmp(&a.clone()), std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let vec: ArrayVec<i32, 4> = (0..4).collect();
+        let mut iter = vec.into_iter();
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next_back(), Some(3));
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+    }
+
+    #[test]
+    fn test_iterator_slices_and_keep_rest() {
+        let vec: ArrayVec<i32, 4> = (0..4).collect();
+        let mut iter = vec.into_iter();
+        iter.next();
+        assert_eq!(iter.as_slice(), &[1, 2, 3]);
+        assert_eq!(iter.len(), 3);
+        iter.by_ref().for_each(drop);
+        assert_eq!(iter.next(), None);
+
+        let mut vec: ArrayVec<i32, 8> = (0..6).collect();
+        let mut drain = vec.drain(1..4);
+        assert_eq!(drain.next(), Some(1));
+        assert_eq!(drain.as_slice(), &[2, 3]);
+        assert_eq!(drain.len(), 2);
+        drain.keep_rest();
+        assert_eq!(vec.as_slice(), &[0, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_drops_elements() {
+        let tracker = Rc::new(());
+        {
+            let mut vec: ArrayVec<Rc<()>, 4> = ArrayVec::new();
+            for _ in 0..4 {
+                vec.push(Rc::clone(&tracker)).unwrap();
+            }
+            assert!(vec.push(Rc::clone(&tracker)).is_err());
+            assert_eq!(Rc::strong_count(&tracker), 5);
+            drop(vec.drain(0..1));
+            assert_eq!(Rc::strong_count(&tracker), 4);
+            let mut iter = vec.into_iter();
+            iter.next();
+        }
+        assert_eq!(Rc::strong_count(&tracker), 1);
+    }
+}
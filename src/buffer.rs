@@ -0,0 +1,198 @@
+use std::ops::Range;
+use std::{mem, ptr, slice};
+
+/// Contiguous element storage: a pointer to the elements and a length that
+/// can be set independently of them. The owning iterators (`IntoIter`,
+/// `Drain`, `Splice`, `ExtractIf`) and the in-place algorithms below are
+/// written once against this trait and shared by every vector that keeps
+/// its elements in a single slice.
+///
+/// Public only so it can bound those iterator types. It lives in a private
+/// module, so nothing outside the crate can name or implement it.
+///
+/// # Safety
+///
+/// The pointers must stay valid for `capacity` slots until the next call to
+/// `reserve_total`, and `reserve_total` must preserve every slot below the
+/// old capacity, including slots past `len`.
+pub unsafe trait Buffer {
+    type Item;
+
+    // Never null, even before the first allocation
+    fn as_ptr(&self) -> *const Self::Item;
+
+    fn as_mut_ptr(&mut self) -> *mut Self::Item;
+
+    fn len(&self) -> usize;
+
+    /// # Safety
+    ///
+    /// `len` must fit the capacity and the elements below it must be
+    /// initialized.
+    unsafe fn set_len(&mut self, len: usize);
+
+    // Makes room for `total` slots counted from the start of the buffer.
+    // A Drain may have parked its tail past `len`, so that has to survive.
+    fn reserve_total(&mut self, total: usize);
+
+    // Drops the elements past `len` in place. The length is updated first, so
+    // a panicking destructor can't cause a double drop; the rest of the slice
+    // is still dropped while unwinding.
+    fn drop_tail(&mut self, len: usize) {
+        let old_len = self.len();
+        if len >= old_len {
+            return;
+        }
+        unsafe {
+            let tail = ptr::slice_from_raw_parts_mut(self.as_mut_ptr().add(len), old_len - len);
+            self.set_len(len);
+            ptr::drop_in_place(tail);
+        }
+    }
+
+    // Appends clones of `items`, reserving once up front
+    fn extend_cloned(&mut self, items: &[Self::Item])
+    where
+        Self::Item: Clone,
+    {
+        let len = self.len();
+        self.reserve_total(len.checked_add(items.len()).expect("capacity overflow"));
+        let ptr = self.as_mut_ptr();
+
+        // Without drop glue a bitwise copy is a valid value that can be
+        // overwritten without leaking anything, so fill the slots with one
+        // memcpy and let `clone_from_slice` run over them. std lowers that to
+        // a memcpy for plain `Copy` types; anything else still has every
+        // `clone` called.
+        if !mem::needs_drop::<Self::Item>() {
+            unsafe {
+                let dst = ptr.add(len);
+                ptr::copy_nonoverlapping(items.as_ptr(), dst, items.len());
+                slice::from_raw_parts_mut(dst, items.len()).clone_from_slice(items);
+                self.set_len(len + items.len());
+            }
+            return;
+        }
+
+        // Bump len per element so a panicking clone leaves a valid buffer
+        for (i, item) in items.iter().enumerate() {
+            unsafe {
+                ptr.add(len + i).write(item.clone());
+                self.set_len(len + i + 1);
+            }
+        }
+    }
+
+    // Appends clones of the elements in `range`, which must be in bounds
+    fn extend_cloned_within(&mut self, range: Range<usize>)
+    where
+        Self::Item: Clone,
+    {
+        let len = self.len();
+        self.reserve_total(len.checked_add(range.len()).expect("capacity overflow"));
+
+        // Bump len per element so a panicking clone leaves a valid buffer
+        let ptr = self.as_mut_ptr();
+        for (i, src) in range.enumerate() {
+            unsafe {
+                let item = (*ptr.add(src)).clone();
+                ptr.add(len + i).write(item);
+                self.set_len(len + i + 1);
+            }
+        }
+    }
+
+    // Makes the buffer a clone of `source`, reusing the existing elements
+    // with `clone_from` and only cloning the rest fresh
+    fn assign_cloned(&mut self, source: &[Self::Item])
+    where
+        Self::Item: Clone,
+    {
+        self.drop_tail(source.len());
+        let (init, tail) = source.split_at(self.len());
+        let prefix = unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), init.len()) };
+        prefix.clone_from_slice(init);
+        self.extend_cloned(tail);
+    }
+
+    // Removes consecutive elements for which `same_bucket(current, previous)`
+    // returns true, keeping the first of each run. Compacts in place and
+    // never reallocates.
+    fn dedup_in_place<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(&mut Self::Item, &mut Self::Item) -> bool,
+    {
+        let len = self.len();
+        if len <= 1 {
+            return;
+        }
+
+        // Closes the gap between the kept prefix and the unvisited elements,
+        // also when `same_bucket` or a destructor panics
+        struct FillGapOnDrop<'a, B: Buffer + ?Sized> {
+            read: usize,   // Next element to visit
+            write: usize,  // End of the kept prefix
+            len: usize,
+            buf: &'a mut B,
+        }
+
+        impl<B: Buffer + ?Sized> Drop for FillGapOnDrop<'_, B> {
+            fn drop(&mut self) {
+                let unvisited = self.len - self.read;
+                unsafe {
+                    let ptr = self.buf.as_mut_ptr();
+                    if self.read != self.write {
+                        ptr::copy(ptr.add(self.read), ptr.add(self.write), unvisited);
+                    }
+                    self.buf.set_len(self.write + unvisited);
+                }
+            }
+        }
+
+        let ptr = self.as_mut_ptr();
+        let mut gap = FillGapOnDrop { read: 1, write: 1, len, buf: self };
+
+        while gap.read < len {
+            unsafe {
+                let current = ptr.add(gap.read);
+                let previous = ptr.add(gap.write - 1);
+                if same_bucket(&mut *current, &mut *previous) {
+                    // Advance first so a panicking destructor isn't run twice
+                    gap.read += 1;
+                    ptr::drop_in_place(current);
+                } else {
+                    if gap.read != gap.write {
+                        ptr::copy_nonoverlapping(current, ptr.add(gap.write), 1);
+                    }
+                    gap.write += 1;
+                    gap.read += 1;
+                }
+            }
+        }
+    }
+}
+
+// Lets the borrowing iterators hold `&mut Vec`-like buffers
+unsafe impl<B: Buffer + ?Sized> Buffer for &mut B {
+    type Item = B::Item;
+
+    fn as_ptr(&self) -> *const B::Item {
+        (**self).as_ptr()
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut B::Item {
+        (**self).as_mut_ptr()
+    }
+
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+
+    unsafe fn set_len(&mut self, len: usize) {
+        (**self).set_len(len)
+    }
+
+    fn reserve_total(&mut self, total: usize) {
+        (**self).reserve_total(total)
+    }
+}
@@ -0,0 +1,158 @@
+// Conversions between this crate's Vec and the std containers. Both sides
+// allocate with the global allocator and `Layout::array::<T>(cap)`, so a
+// buffer can change hands without copying its elements.
+
+use crate::allocator::Global;
+use crate::growth::GrowthPolicy;
+use crate::Vec;
+use std::mem::ManuallyDrop;
+use std::string::FromUtf8Error;
+
+impl<T, G: GrowthPolicy> Vec<T, Global, G> {
+    /// Converts into a boxed slice, reusing the buffer. Excess capacity is
+    /// released first, which may reallocate.
+    pub fn into_boxed_slice(self) -> Box<[T]> {
+        std::vec::Vec::from(self).into_boxed_slice()
+    }
+}
+
+impl<T> From<std::vec::Vec<T>> for Vec<T> {
+    fn from(vec: std::vec::Vec<T>) -> Self {
+        let mut vec = ManuallyDrop::new(vec);
+        let (ptr, len, cap) = (vec.as_mut_ptr(), vec.len(), vec.capacity());
+        unsafe { Vec::from_raw_parts(ptr, len, cap) }
+    }
+}
+
+impl<T, G: GrowthPolicy> From<Vec<T, Global, G>> for std::vec::Vec<T> {
+    fn from(vec: Vec<T, Global, G>) -> Self {
+        let (ptr, len, cap, _) = vec.into_raw_parts_with_alloc();
+        unsafe { std::vec::Vec::from_raw_parts(ptr, len, cap) }
+    }
+}
+
+impl<T> From<Box<[T]>> for Vec<T> {
+    fn from(slice: Box<[T]>) -> Self {
+        Vec::from(slice.into_vec())
+    }
+}
+
+impl<T: Clone> From<&[T]> for Vec<T> {
+    // Borrowed data has to be copied
+    fn from(slice: &[T]) -> Self {
+        let mut vec = Vec::new();
+        vec.extend_from_slice(slice);
+        vec
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for Vec<T> {
+    fn from(array: [T; N]) -> Self {
+        if N == 0 {
+            return Vec::new();
+        }
+
+        // Move the elements out with one copy; the array must not drop them
+        let array = ManuallyDrop::new(array);
+        let mut vec = Vec::with_capacity(N);
+        unsafe {
+            std::ptr::copy_nonoverlapping(array.as_ptr(), vec.as_mut_ptr(), N);
+            vec.set_len(N);
+        }
+        vec
+    }
+}
+
+impl From<String> for Vec<u8> {
+    fn from(string: String) -> Self {
+        Vec::from(string.into_bytes())
+    }
+}
+
+// UTF-8 has to be validated, so this is TryFrom rather than From. On failure
+// the error still owns the bytes (`FromUtf8Error::into_bytes`).
+impl<G: GrowthPolicy> TryFrom<Vec<u8, Global, G>> for String {
+    type Error = FromUtf8Error;
+
+    fn try_from(bytes: Vec<u8, Global, G>) -> Result<Self, Self::Error> {
+        String::from_utf8(std::vec::Vec::from(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_std_vec_round_trip_keeps_buffer() {
+        let mut std_vec = std::vec::Vec::with_capacity(32);
+        std_vec.extend(0..10);
+        let ptr = std_vec.as_ptr();
+
+        let vec: Vec<i32> = Vec::from(std_vec);
+        assert_eq!(vec.as_ptr(), ptr);
+        assert_eq!(vec.capacity(), 32);
+        assert_eq!(vec.len(), 10);
+
+        let back: std::vec::Vec<i32> = vec.into();
+        assert_eq!(back.as_ptr(), ptr);
+        assert_eq!(back, (0..10).collect::<std::vec::Vec<_>>());
+    }
+
+    #[test]
+    fn test_empty_and_zero_sized() {
+        let vec: Vec<String> = Vec::from(std::vec::Vec::new());
+        assert!(vec.is_empty());
+        let back: std::vec::Vec<String> = vec.into();
+        assert!(back.is_empty());
+
+        let units: Vec<()> = Vec::from(vec![(); 5]);
+        assert_eq!(units.len(), 5);
+        assert_eq!(std::vec::Vec::from(units).len(), 5);
+    }
+
+    #[test]
+    fn test_boxed_slice() {
+        let mut vec: Vec<String> = Vec::with_capacity(8);
+        vec.push("a".to_string());
+        vec.push("b".to_string());
+
+        let boxed = vec.into_boxed_slice();
+        assert_eq!(&*boxed, &["a", "b"]);
+
+        let ptr = boxed.as_ptr();
+        let vec = Vec::from(boxed);
+        assert_eq!(vec.as_ptr(), ptr);
+        assert_eq!(vec.capacity(), 2);
+    }
+
+    #[test]
+    fn test_from_slice_and_array() {
+        let vec = Vec::from(&["x".to_string(), "y".to_string()][..]);
+        assert_eq!(vec.as_slice(), &["x", "y"]);
+
+        let vec = Vec::from([String::from("moved"), String::from("twice")]);
+        assert_eq!(vec.as_slice(), &["moved", "twice"]);
+        assert_eq!(vec.capacity(), 2);
+
+        let empty: Vec<u8> = Vec::from([]);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_string_bytes() {
+        let string = String::from("hello");
+        let ptr = string.as_ptr();
+        let bytes = Vec::from(string);
+        assert_eq!(bytes.as_ptr(), ptr);
+        assert_eq!(bytes.as_slice(), b"hello");
+
+        let string = String::try_from(bytes).unwrap();
+        assert_eq!(string, "hello");
+        assert_eq!(string.as_ptr(), ptr);
+
+        let invalid = Vec::from(&[0xff, 0xfe][..]);
+        let err = String::try_from(invalid).unwrap_err();
+        assert_eq!(err.into_bytes(), [0xff, 0xfe]);
+    }
+}
@@ -0,0 +1,119 @@
+use std::alloc::Layout;
+use std::fmt;
+
+/// Error returned by the fallible allocation methods (`try_reserve`, `try_push`, ...).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryReserveError {
+    /// The requested capacity exceeds the platform maximum or overflows `usize`.
+    CapacityOverflow,
+    /// The allocator could not provide memory for `layout`.
+    AllocError { layout: Layout },
+}
+
+impl fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("memory allocation failed")?;
+        match self {
+            TryReserveError::CapacityOverflow => {
+                f.write_str(" because the computed capacity exceeded the collection's maximum")
+            }
+            TryReserveError::AllocError { layout } => write!(
+                f,
+                " because the allocator returned an error (size {}, align {})",
+                layout.size(),
+                layout.align()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TryReserveError {}
+
+/// Error returned when a fixed-capacity container is full. Hands back the
+/// element that could not be stored.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError<T> {
+    element: T,
+}
+
+impl<T> CapacityError<T> {
+    pub fn new(element: T) -> Self {
+        CapacityError { element }
+    }
+
+    pub fn element(&self) -> &T {
+        &self.element
+    }
+
+    pub fn into_inner(self) -> T {
+        self.element
+    }
+}
+
+// Doesn't require T: Debug, so `push(x).unwrap()` works for any T
+impl<T> fmt::Debug for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CapacityError: insufficient capacity")
+    }
+}
+
+impl<T> fmt::Display for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("insufficient capacity")
+    }
+}
+
+impl<T> std::error::Error for CapacityError<T> {}
+
+/// Error returned by `get_many_mut`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GetManyMutError {
+    /// One of the indices is past the end of the vector.
+    IndexOutOfBounds { index: usize, len: usize },
+    /// The same index was requested twice.
+    OverlappingIndices { index: usize },
+}
+
+impl fmt::Display for GetManyMutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GetManyMutError::IndexOutOfBounds { index, len } => {
+                write!(f, "index (is {}) should be < len (is {})", index, len)
+            }
+            GetManyMutError::OverlappingIndices { index } => {
+                write!(f, "index (is {}) was requested more than once", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GetManyMutError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let overflow = TryReserveError::CapacityOverflow;
+        assert!(overflow.to_string().contains("exceeded the collection's maximum"));
+
+        let layout = Layout::array::<u32>(4).unwrap();
+        let alloc = TryReserveError::AllocError { layout };
+        assert!(alloc.to_string().contains("size 16, align 4"));
+    }
+
+    #[test]
+    fn test_capacity_error_returns_element() {
+        let err = CapacityError::new(String::from("rejected"));
+        assert_eq!(err.element(), "rejected");
+        assert_eq!(err.to_string(), "insufficient capacity");
+        assert_eq!(err.into_inner(), "rejected");
+    }
+
+    #[test]
+    fn test_get_many_mut_error_display() {
+        let err = GetManyMutError::IndexOutOfBounds { index: 7, len: 3 };
+        assert_eq!(err.to_string(), "index (is 7) should be < len (is 3)");
+    }
+}
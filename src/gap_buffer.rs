@@ -0,0 +1,452 @@
+use crate::allocator::{Allocator, Global};
+use crate::growth::{GrowthPolicy, TypeSizeAware};
+use crate::raw_vec::RawVec;
+use crate::vec_deque::{DropSlice, Iter, IterMut};
+use crate::{iter, Vec};
+use std::mem::ManuallyDrop;
+use std::ops::{Index, IndexMut};
+use std::{fmt, ptr};
+
+/// A buffer with a movable gap of spare capacity at the cursor, for
+/// editor-style workloads. Inserting or removing at the gap is O(1); moving
+/// the gap costs one copy of the elements it passes over.
+///
+/// The elements live in `[0, gap_start)` and `[gap_end, capacity)`.
+pub struct GapBuffer<T, A: Allocator = Global, G: GrowthPolicy = TypeSizeAware> {
+    buf: RawVec<T, A, G>,
+    gap_start: usize, // First free slot, and the cursor position
+    gap_end: usize,   // One past the last free slot
+}
+
+impl<T> GapBuffer<T> {
+    pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+}
+
+impl<T, A: Allocator> GapBuffer<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_policy_in(TypeSizeAware, alloc)
+    }
+
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        let buf = RawVec::with_capacity_in(capacity, alloc);
+        let gap_end = buf.capacity();
+        GapBuffer { buf, gap_start: 0, gap_end }
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> GapBuffer<T, A, G> {
+    pub fn with_policy_in(policy: G, alloc: A) -> Self {
+        let buf = RawVec::with_policy_in(policy, alloc);
+        let gap_end = buf.capacity();
+        GapBuffer { buf, gap_start: 0, gap_end }
+    }
+
+    pub fn len(&self) -> usize {
+        self.capacity() - self.gap_len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.buf.allocator()
+    }
+
+    /// Index the gap currently sits at: the next `insert` there is O(1).
+    pub fn gap_position(&self) -> usize {
+        self.gap_start
+    }
+
+    fn gap_len(&self) -> usize {
+        self.gap_end - self.gap_start
+    }
+
+    // RawVec leaves the pointer null until the first allocation
+    fn ptr(&self) -> *mut T {
+        let ptr = self.buf.ptr();
+        if ptr.is_null() {
+            ptr::NonNull::dangling().as_ptr()
+        } else {
+            ptr
+        }
+    }
+
+    // Buffer slot of the element at logical `index`
+    fn to_physical(&self, index: usize) -> usize {
+        if index < self.gap_start {
+            index
+        } else {
+            index + self.gap_len()
+        }
+    }
+
+    /// Makes the gap at least `additional` slots wide, growing at most once.
+    pub fn reserve(&mut self, additional: usize) {
+        let gap_len = self.gap_len();
+        if additional <= gap_len {
+            return;
+        }
+
+        // The buffer grows at its end, so the elements after the gap move
+        // up to keep the new space inside the gap
+        let old_cap = self.capacity();
+        self.buf.reserve(additional - gap_len);
+        let tail_len = old_cap - self.gap_end;
+        let new_gap_end = self.capacity() - tail_len;
+        unsafe { ptr::copy(self.ptr().add(self.gap_end), self.ptr().add(new_gap_end), tail_len) };
+        self.gap_end = new_gap_end;
+    }
+
+    /// Moves the gap to `index`, copying the elements between the old and
+    /// new position across it.
+    pub fn move_gap_to(&mut self, index: usize) {
+        let len = self.len();
+        if index > len {
+            panic!("gap position (is {}) should be <= len (is {})", index, len);
+        }
+
+        let ptr = self.ptr();
+        unsafe {
+            if index < self.gap_start {
+                // [index, gap_start) moves to the far side of the gap
+                let count = self.gap_start - index;
+                ptr::copy(ptr.add(index), ptr.add(self.gap_end - count), count);
+                self.gap_end -= count;
+            } else {
+                // [gap_end, gap_end + count) moves to the near side
+                let count = index - self.gap_start;
+                ptr::copy(ptr.add(self.gap_end), ptr.add(self.gap_start), count);
+                self.gap_end += count;
+            }
+        }
+        self.gap_start = index;
+    }
+
+    /// Inserts `item` at `index`, moving the gap there first. The gap stays
+    /// just after the new element, so consecutive inserts are O(1).
+    pub fn insert(&mut self, index: usize, item: T) {
+        let len = self.len();
+        if index > len {
+            panic!("insertion index (is {}) should be <= len (is {})", index, len);
+        }
+        self.move_gap_to(index);
+        self.reserve(1);
+        self.buf.write_at(self.gap_start, item);
+        self.gap_start += 1;
+    }
+
+    /// Removes and returns the element at `index`, moving the gap there.
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len();
+        if index >= len {
+            panic!("removal index (is {}) should be < len (is {})", index, len);
+        }
+        self.move_gap_to(index);
+        let item = self.buf.read_at(self.gap_end);
+        self.gap_end += 1;
+        item
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.insert(self.len(), item);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        match self.len() {
+            0 => None,
+            len => Some(self.remove(len - 1)),
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len() {
+            Some(self.buf.get_ref(self.to_physical(index)))
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len() {
+            let slot = self.to_physical(index);
+            Some(self.buf.get_mut(slot))
+        } else {
+            None
+        }
+    }
+
+    pub fn clear(&mut self) {
+        let (front, back) = self.raw_slices();
+
+        // Empty before dropping, so a panicking destructor can't double-drop
+        self.gap_start = 0;
+        self.gap_end = self.capacity();
+        let _back = DropSlice(back);
+        unsafe { ptr::drop_in_place(front) };
+    }
+
+    fn raw_slices(&self) -> (*mut [T], *mut [T]) {
+        let ptr = self.ptr();
+        unsafe {
+            (
+                ptr::slice_from_raw_parts_mut(ptr, self.gap_start),
+                ptr::slice_from_raw_parts_mut(ptr.add(self.gap_end), self.capacity() - self.gap_end),
+            )
+        }
+    }
+
+    /// The elements before and after the gap.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (front, back) = self.raw_slices();
+        unsafe { (&*front, &*back) }
+    }
+
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (front, back) = self.raw_slices();
+        unsafe { (&mut *front, &mut *back) }
+    }
+
+    /// Moves the gap to the end so the elements form one slice, and returns
+    /// it. Costs one copy of the elements after the gap; free if the gap is
+    /// already at the end.
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        self.move_gap_to(self.len());
+        self.as_mut_slices().0
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
+        Iter::new(front, back)
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (front, back) = self.as_mut_slices();
+        IterMut::new(front, back)
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Drop for GapBuffer<T, A, G> {
+    fn drop(&mut self) {
+        let (front, back) = self.raw_slices();
+        let _back = DropSlice(back);
+        unsafe { ptr::drop_in_place(front) };
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> From<Vec<T, A, G>> for GapBuffer<T, A, G> {
+    /// Adopts the vector's buffer, with its spare capacity as the gap at the
+    /// end. O(1).
+    fn from(vec: Vec<T, A, G>) -> Self {
+        let vec = ManuallyDrop::new(vec);
+        let buf = unsafe { ptr::read(&vec.buf) };
+        let gap_end = buf.capacity();
+        GapBuffer { buf, gap_start: vec.len, gap_end }
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> From<GapBuffer<T, A, G>> for Vec<T, A, G> {
+    /// Compacts the gap to the end and hands the buffer over without
+    /// reallocating.
+    fn from(mut buffer: GapBuffer<T, A, G>) -> Self {
+        let len = buffer.make_contiguous().len();
+        let buffer = ManuallyDrop::new(buffer);
+        Vec { buf: unsafe { ptr::read(&buffer.buf) }, len }
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Index<usize> for GapBuffer<T, A, G> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(item) => item,
+            None => panic!("index (is {}) should be < len (is {})", index, self.len()),
+        }
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> IndexMut<usize> for GapBuffer<T, A, G> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len();
+        match self.get_mut(index) {
+            Some(item) => item,
+            None => panic!("index (is {}) should be < len (is {})", index, len),
+        }
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone, G: GrowthPolicy + Clone> Clone for GapBuffer<T, A, G> {
+    // The clone starts with its gap at the end
+    fn clone(&self) -> Self {
+        let mut vec = Vec::with_policy_in(self.buf.policy().clone(), self.allocator().clone());
+        vec.extend(self.iter().cloned());
+        GapBuffer::from(vec)
+    }
+}
+
+impl<T: fmt::Debug, A: Allocator, G: GrowthPolicy> fmt::Debug for GapBuffer<T, A, G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, A: Allocator, G: GrowthPolicy> PartialEq for GapBuffer<T, A, G> {
+    // Equal contents compare equal wherever their gaps are
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, A: Allocator, G: GrowthPolicy> Eq for GapBuffer<T, A, G> {}
+
+impl<T> Default for GapBuffer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<T> for GapBuffer<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        GapBuffer::from(Vec::from_iter(iter))
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> IntoIterator for GapBuffer<T, A, G> {
+    type Item = T;
+    type IntoIter = iter::IntoIter<Vec<T, A, G>>;
+
+    // Compacts once, then iterates as a Vec
+    fn into_iter(self) -> Self::IntoIter {
+        Vec::from(self).into_iter()
+    }
+}
+
+impl<'a, T, A: Allocator, G: GrowthPolicy> IntoIterator for &'a GapBuffer<T, A, G> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, A: Allocator, G: GrowthPolicy> IntoIterator for &'a mut GapBuffer<T, A, G> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(buffer: &GapBuffer<char>) -> String {
+        buffer.iter().collect()
+    }
+
+    #[test]
+    fn test_typing_at_cursor() {
+        let mut buffer: GapBuffer<char> = "hello world".chars().collect();
+        assert_eq!(buffer.gap_position(), 11);
+
+        buffer.move_gap_to(5);
+        for c in ", big".chars() {
+            buffer.insert(buffer.gap_position(), c);
+        }
+        assert_eq!(text(&buffer), "hello, big world");
+        let (before, after) = buffer.as_slices();
+        assert_eq!(before.iter().collect::<String>(), "hello, big");
+        assert_eq!(after.iter().collect::<String>(), " world");
+
+        // Backspace over " big"
+        for _ in 0..4 {
+            let cursor = buffer.gap_position();
+            buffer.remove(cursor - 1);
+        }
+        assert_eq!(text(&buffer), "hello, world");
+        assert_eq!(buffer.gap_position(), 6);
+    }
+
+    #[test]
+    fn test_growth_keeps_gap_at_cursor() {
+        let mut buffer = GapBuffer::with_policy_in(crate::Exact, Global);
+        buffer.reserve(4);
+        for i in 0..4 {
+            buffer.push(i);
+        }
+        buffer.move_gap_to(1);
+        assert_eq!(buffer.capacity(), 4);
+
+        buffer.insert(1, 10);
+        assert_eq!(buffer.capacity(), 5);
+        assert_eq!(buffer.as_slices(), (&[0, 10][..], &[1, 2, 3][..]));
+
+        buffer.reserve(3);
+        assert_eq!(buffer.capacity(), 8);
+        assert_eq!(buffer.gap_position(), 2);
+        assert_eq!(buffer.as_slices(), (&[0, 10][..], &[1, 2, 3][..]));
+    }
+
+    #[test]
+    fn test_make_contiguous_and_vec_round_trip() {
+        let vec: Vec<i32> = (0..6).collect();
+        let ptr = vec.as_ptr();
+        let mut buffer = GapBuffer::from(vec);
+        buffer.move_gap_to(2);
+        buffer.remove(2);
+        assert_eq!(buffer.as_slices(), (&[0, 1][..], &[3, 4, 5][..]));
+
+        assert_eq!(buffer.make_contiguous(), &[0, 1, 3, 4, 5]);
+        assert_eq!(buffer.gap_position(), 5);
+
+        buffer.move_gap_to(0);
+        let vec = Vec::from(buffer);
+        assert_eq!(vec.as_ptr(), ptr);
+        assert_eq!(vec, [0, 1, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_indexing_across_gap() {
+        let mut buffer: GapBuffer<i32> = (0..5).collect();
+        buffer.move_gap_to(2);
+        buffer[3] = 30;
+        assert_eq!(buffer[1], 1);
+        assert_eq!(buffer[3], 30);
+        assert_eq!(buffer.get(5), None);
+        assert_eq!(buffer.pop(), Some(4));
+        assert_eq!(buffer.into_iter().collect::<std::vec::Vec<_>>(), [0, 1, 2, 30]);
+    }
+
+    #[test]
+    #[should_panic(expected = "gap position (is 4) should be <= len (is 3)")]
+    fn test_move_gap_past_end() {
+        let mut buffer: GapBuffer<i32> = (0..3).collect();
+        buffer.move_gap_to(4);
+    }
+
+    #[test]
+    fn test_zero_sized() {
+        let mut buffer = GapBuffer::new();
+        for i in 0..10 {
+            buffer.insert(i / 2, ());
+        }
+        buffer.move_gap_to(3);
+        buffer.remove(7);
+        assert_eq!(buffer.len(), 9);
+        assert_eq!(buffer.make_contiguous().len(), 9);
+    }
+}
@@ -0,0 +1,350 @@
+/// Decides how a `RawVec` grows and shrinks.
+///
+/// The policy is stored in the vector, so it can be picked statically as a
+/// type parameter (`Vec<T, Global, Doubling>`), configured per instance
+/// (`PageRounded::new(65536)`), or chosen at runtime with
+/// `Box<dyn GrowthPolicy>`.
+pub trait GrowthPolicy {
+    /// New capacity when growing from `cap` (possibly 0) so that at least
+    /// `required` elements fit. Must return a value `>= required`.
+    fn grow(&self, cap: usize, required: usize, elem_size: usize) -> usize;
+
+    /// Smallest capacity allocated for a non-empty buffer.
+    fn min_capacity(&self, _elem_size: usize) -> usize {
+        1
+    }
+
+    /// Adjusts a candidate capacity (e.g. to a power of two or page boundary).
+    fn round(&self, cap: usize, _elem_size: usize) -> usize {
+        cap
+    }
+
+    /// Capacity to shrink to when holding `len` elements, or `None` to keep
+    /// the current buffer. Must return a value in `len..cap` when `Some`.
+    fn shrink(&self, cap: usize, len: usize, elem_size: usize) -> Option<usize> {
+        // Default: only shrink when less than 25% of the buffer is used
+        if len >= cap / 4 {
+            return None;
+        }
+        let new_cap = self.round(std::cmp::max(len, self.min_capacity(elem_size)), elem_size);
+        if new_cap < cap { Some(new_cap) } else { None }
+    }
+}
+
+impl<P: GrowthPolicy + ?Sized> GrowthPolicy for &P {
+    fn grow(&self, cap: usize, required: usize, elem_size: usize) -> usize {
+        (**self).grow(cap, required, elem_size)
+    }
+
+    fn min_capacity(&self, elem_size: usize) -> usize {
+        (**self).min_capacity(elem_size)
+    }
+
+    fn round(&self, cap: usize, elem_size: usize) -> usize {
+        (**self).round(cap, elem_size)
+    }
+
+    fn shrink(&self, cap: usize, len: usize, elem_size: usize) -> Option<usize> {
+        (**self).shrink(cap, len, elem_size)
+    }
+}
+
+impl<P: GrowthPolicy + ?Sized> GrowthPolicy for Box<P> {
+    fn grow(&self, cap: usize, required: usize, elem_size: usize) -> usize {
+        (**self).grow(cap, required, elem_size)
+    }
+
+    fn min_capacity(&self, elem_size: usize) -> usize {
+        (**self).min_capacity(elem_size)
+    }
+
+    fn round(&self, cap: usize, elem_size: usize) -> usize {
+        (**self).round(cap, elem_size)
+    }
+
+    fn shrink(&self, cap: usize, len: usize, elem_size: usize) -> Option<usize> {
+        (**self).shrink(cap, len, elem_size)
+    }
+}
+
+/// The default policy: growth rate depends on element size.
+///
+/// - Small types (<=128B): 100% growth with power-of-2 rounding
+/// - Medium types (128B-1KB): 50% growth, no rounding
+/// - Large types (>1KB): 25% growth, no rounding
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TypeSizeAware;
+
+impl TypeSizeAware {
+    pub(crate) const MIN_NON_ZERO_CAP: usize = 8;  // Minimum non-zero capacity
+    const SHRINK_THRESHOLD: f64 = 0.25;  // Shrink when waste > 25%
+    const MAX_EXCESS_CAPACITY: usize = 1024 * 1024;  // 1MB worth of elements
+
+    fn should_shrink(cap: usize, len: usize, elem_size: usize) -> bool {
+        // Calculate waste
+        let waste = cap - len;
+        let waste_bytes = waste * elem_size;
+
+        // Calculate usage ratio
+        let usage_ratio = (len as f64) / (cap as f64);
+        let waste_ratio = 1.0 - usage_ratio;
+
+        // Shrink if either:
+        // 1. We're wasting significant memory (>25% AND >1MB)
+        // 2. OR we're using less than 25% of capacity (regardless of size)
+        (waste_bytes > Self::MAX_EXCESS_CAPACITY && waste_ratio > Self::SHRINK_THRESHOLD) ||
+        (usage_ratio < Self::SHRINK_THRESHOLD)
+    }
+}
+
+impl GrowthPolicy for TypeSizeAware {
+    fn grow(&self, cap: usize, required: usize, elem_size: usize) -> usize {
+        // Starting from zero: at least MIN_NON_ZERO_CAP, always a power of 2
+        if cap == 0 {
+            return std::cmp::max(required, self.min_capacity(elem_size)).next_power_of_two();
+        }
+
+        // Calculate minimum growth based on element size
+        let growth = if elem_size > 1024 {
+            // Large elements (>1KB): grow by 25%
+            cap.saturating_add(cap / 4)
+        } else if elem_size > 128 {
+            // Medium elements (>128B): grow by 50%
+            cap.saturating_add(cap / 2)
+        } else {
+            // Small elements: grow by 100%
+            cap.saturating_mul(2)
+        };
+
+        self.round(std::cmp::max(growth, required), elem_size)
+    }
+
+    fn min_capacity(&self, _elem_size: usize) -> usize {
+        Self::MIN_NON_ZERO_CAP
+    }
+
+    fn round(&self, cap: usize, elem_size: usize) -> usize {
+        // Only round to power of 2 for small types
+        if elem_size <= 128 {
+            let next_pow2 = cap.next_power_of_two();
+            let waste = next_pow2.saturating_sub(cap);
+            if next_pow2 <= cap.saturating_add(cap / 8) && // Within 12.5%
+               waste * elem_size <= 16 * 1024 {  // Waste <= 16KB
+                return next_pow2;
+            }
+        }
+        cap
+    }
+
+    fn shrink(&self, cap: usize, len: usize, elem_size: usize) -> Option<usize> {
+        // Don't shrink if length is close to capacity
+        if len >= cap - cap / 4 {
+            return None;
+        }
+
+        // Only shrink if we should according to our criteria
+        if !Self::should_shrink(cap, len, elem_size) {
+            return None;
+        }
+
+        // Round up to next power of 2 above len, but not below MIN_NON_ZERO_CAP
+        let new_cap = std::cmp::max(len.next_power_of_two(), Self::MIN_NON_ZERO_CAP);
+        if new_cap < cap { Some(new_cap) } else { None }
+    }
+}
+
+/// Classic 2x growth with the same minimum capacities as `std::vec::Vec`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Doubling;
+
+impl GrowthPolicy for Doubling {
+    fn grow(&self, cap: usize, required: usize, elem_size: usize) -> usize {
+        let doubled = cap.saturating_mul(2);
+        std::cmp::max(std::cmp::max(doubled, required), self.min_capacity(elem_size))
+    }
+
+    fn min_capacity(&self, elem_size: usize) -> usize {
+        if elem_size == 1 {
+            8
+        } else if elem_size <= 1024 {
+            4
+        } else {
+            1
+        }
+    }
+}
+
+/// Grows along the Fibonacci sequence (~1.618x), trading a few more
+/// reallocations for less slack than doubling.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Fibonacci;
+
+impl GrowthPolicy for Fibonacci {
+    fn grow(&self, cap: usize, required: usize, elem_size: usize) -> usize {
+        let required = std::cmp::max(required, self.min_capacity(elem_size));
+
+        // Next Fibonacci number above the current capacity that fits `required`
+        let (mut a, mut b) = (1usize, 2usize);
+        while b <= cap || b < required {
+            if b == usize::MAX {
+                break;
+            }
+            (a, b) = (b, a.saturating_add(b));
+        }
+        b
+    }
+
+    fn min_capacity(&self, _elem_size: usize) -> usize {
+        8
+    }
+}
+
+/// Allocates exactly what is required: no slack, one reallocation per growth.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Exact;
+
+impl GrowthPolicy for Exact {
+    fn grow(&self, _cap: usize, required: usize, _elem_size: usize) -> usize {
+        required
+    }
+
+    fn shrink(&self, cap: usize, len: usize, _elem_size: usize) -> Option<usize> {
+        let new_cap = std::cmp::max(len, 1);
+        if new_cap < cap { Some(new_cap) } else { None }
+    }
+}
+
+/// Doubles, then rounds every buffer up to a whole number of pages so the
+/// allocator never hands back a partially used page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRounded {
+    page_size: usize,
+}
+
+impl PageRounded {
+    pub const DEFAULT_PAGE_SIZE: usize = 4096;
+
+    pub fn new(page_size: usize) -> Self {
+        assert!(page_size.is_power_of_two(), "page size (is {}) should be a power of two", page_size);
+        PageRounded { page_size }
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+}
+
+impl Default for PageRounded {
+    fn default() -> Self {
+        PageRounded::new(Self::DEFAULT_PAGE_SIZE)
+    }
+}
+
+impl GrowthPolicy for PageRounded {
+    fn grow(&self, cap: usize, required: usize, elem_size: usize) -> usize {
+        let doubled = cap.saturating_mul(2);
+        let candidate = std::cmp::max(std::cmp::max(doubled, required), self.min_capacity(elem_size));
+        self.round(candidate, elem_size)
+    }
+
+    fn min_capacity(&self, elem_size: usize) -> usize {
+        // One full page
+        std::cmp::max(1, self.page_size / elem_size.max(1))
+    }
+
+    fn round(&self, cap: usize, elem_size: usize) -> usize {
+        let elem_size = elem_size.max(1);
+        let bytes = match cap.checked_mul(elem_size) {
+            Some(bytes) => bytes,
+            None => return cap,
+        };
+        let pages = bytes.div_ceil(self.page_size);
+        match pages.checked_mul(self.page_size) {
+            // Whole elements only: never round below `cap`
+            Some(rounded) => std::cmp::max(rounded / elem_size, cap),
+            None => cap,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_size_aware_small() {
+        let policy = TypeSizeAware;
+        assert_eq!(policy.grow(0, 1, 4), 8);
+        assert_eq!(policy.grow(0, 1000, 1), 1024);
+        assert_eq!(policy.grow(8, 9, 4), 16);
+    }
+
+    #[test]
+    fn test_type_size_aware_medium_and_large() {
+        let policy = TypeSizeAware;
+        assert_eq!(policy.grow(16, 17, 512), 24);
+        assert_eq!(policy.grow(16, 17, 2048), 20);
+        // Growth never falls short of what was asked for
+        assert_eq!(policy.grow(16, 100, 2048), 100);
+    }
+
+    #[test]
+    fn test_type_size_aware_shrink() {
+        let policy = TypeSizeAware;
+        assert_eq!(policy.shrink(1024, 128, 4), Some(128));
+        assert_eq!(policy.shrink(1024, 900, 4), None);
+        assert_eq!(policy.shrink(16, 1, 4), Some(8));
+        assert_eq!(policy.shrink(8, 1, 4), None);
+    }
+
+    #[test]
+    fn test_doubling() {
+        let policy = Doubling;
+        assert_eq!(policy.grow(0, 1, 1), 8);
+        assert_eq!(policy.grow(0, 1, 4), 4);
+        assert_eq!(policy.grow(0, 1, 4096), 1);
+        assert_eq!(policy.grow(4, 5, 4), 8);
+        assert_eq!(policy.grow(4, 20, 4), 20);
+    }
+
+    #[test]
+    fn test_fibonacci() {
+        let policy = Fibonacci;
+        assert_eq!(policy.grow(0, 1, 4), 8);
+        assert_eq!(policy.grow(8, 9, 4), 13);
+        assert_eq!(policy.grow(13, 14, 4), 21);
+        assert_eq!(policy.grow(21, 100, 4), 144);
+    }
+
+    #[test]
+    fn test_exact() {
+        let policy = Exact;
+        assert_eq!(policy.grow(0, 3, 4), 3);
+        assert_eq!(policy.grow(3, 4, 4), 4);
+        assert_eq!(policy.shrink(10, 4, 4), Some(4));
+        assert_eq!(policy.shrink(4, 4, 4), None);
+    }
+
+    #[test]
+    fn test_page_rounded() {
+        let policy = PageRounded::default();
+        assert_eq!(policy.grow(0, 1, 4), 1024);
+        assert_eq!(policy.grow(1024, 1025, 4), 2048);
+        // 24-byte elements don't divide a page evenly: stay within whole pages
+        let cap = policy.grow(0, 1, 24);
+        assert!(cap * 24 <= 4096 && (cap + 1) * 24 > 4096);
+    }
+
+    #[test]
+    #[should_panic(expected = "page size (is 1000) should be a power of two")]
+    fn test_page_rounded_invalid() {
+        PageRounded::new(1000);
+    }
+
+    #[test]
+    fn test_dyn_policy() {
+        let policies: [Box<dyn GrowthPolicy>; 2] = [Box::new(Exact), Box::new(Doubling)];
+        assert_eq!(policies[0].grow(4, 5, 4), 5);
+        assert_eq!(policies[1].grow(4, 5, 4), 8);
+    }
+}
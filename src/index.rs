@@ -0,0 +1,134 @@
+use std::ops::{
+    Bound, Range, RangeBounds, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive,
+};
+use std::slice::SliceIndex;
+
+use crate::error::GetManyMutError;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Index types accepted by `Vec`'s `Index`/`IndexMut`: `usize` and every
+/// range kind. Sealed; the lookup itself is done by `SliceIndex`, this trait
+/// only reports failures in the crate's "index (is X) should be < len (is Y)"
+/// style.
+pub trait VecIndex<T>: SliceIndex<[T]> + Clone + private::Sealed {
+    #[doc(hidden)]
+    fn out_of_bounds(self, len: usize) -> !;
+}
+
+impl private::Sealed for usize {}
+
+impl<T> VecIndex<T> for usize {
+    fn out_of_bounds(self, len: usize) -> ! {
+        panic!("index (is {}) should be < len (is {})", self, len);
+    }
+}
+
+// All range kinds share one failure path through their bounds
+macro_rules! impl_range_index {
+    ($($range:ty),*) => {$(
+        impl private::Sealed for $range {}
+
+        impl<T> VecIndex<T> for $range {
+            fn out_of_bounds(self, len: usize) -> ! {
+                range_out_of_bounds(self, len)
+            }
+        }
+    )*};
+}
+
+impl_range_index!(
+    Range<usize>,
+    RangeFrom<usize>,
+    RangeFull,
+    RangeInclusive<usize>,
+    RangeTo<usize>,
+    RangeToInclusive<usize>,
+    (Bound<usize>, Bound<usize>)
+);
+
+fn range_out_of_bounds<R: RangeBounds<usize>>(range: R, len: usize) -> ! {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => Some(start),
+        Bound::Excluded(&start) => start.checked_add(1),
+        Bound::Unbounded => Some(0),
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.checked_add(1),
+        Bound::Excluded(&end) => Some(end),
+        Bound::Unbounded => Some(len),
+    };
+
+    // Without an end bound the start can only be past the length
+    let unbounded_end = matches!(range.end_bound(), Bound::Unbounded);
+
+    match (start, end) {
+        (None, _) => panic!("range start index (is usize::MAX) should be <= len (is {})", len),
+        (_, None) => panic!("range end index (is usize::MAX) should be < len (is {})", len),
+        (Some(start), Some(_)) if unbounded_end => {
+            panic!("range start index (is {}) should be <= len (is {})", start, len)
+        }
+        (Some(start), Some(end)) if start > end => panic!(
+            "range start index (is {}) should be <= range end index (is {})",
+            start, end
+        ),
+        (_, Some(end)) => panic!("range end index (is {}) should be <= len (is {})", end, len),
+    }
+}
+
+// Backs `get_many_mut` on every vector type
+pub(crate) fn get_many_mut<T, const N: usize>(
+    slice: &mut [T],
+    indices: [usize; N],
+) -> Result<[&mut T; N], GetManyMutError> {
+    let len = slice.len();
+    for (i, &index) in indices.iter().enumerate() {
+        if index >= len {
+            return Err(GetManyMutError::IndexOutOfBounds { index, len });
+        }
+        if indices[..i].contains(&index) {
+            return Err(GetManyMutError::OverlappingIndices { index });
+        }
+    }
+
+    // Indices are in bounds and pairwise distinct, so the references can't alias
+    let ptr = slice.as_mut_ptr();
+    Ok(indices.map(|index| unsafe { &mut *ptr.add(index) }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "index (is 3) should be < len (is 3)")]
+    fn test_usize_message() {
+        VecIndex::<u8>::out_of_bounds(3usize, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index (is 5) should be <= len (is 3)")]
+    fn test_range_end_message() {
+        VecIndex::<u8>::out_of_bounds(1..=4, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start index (is 3) should be <= range end index (is 1)")]
+    fn test_range_order_message() {
+        VecIndex::<u8>::out_of_bounds((Bound::Included(3), Bound::Excluded(1)), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start index (is usize::MAX) should be <= len (is 3)")]
+    fn test_start_overflow_message() {
+        VecIndex::<u8>::out_of_bounds((Bound::Excluded(usize::MAX), Bound::Unbounded), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index (is usize::MAX) should be < len (is 2)")]
+    fn test_inclusive_overflow_message() {
+        VecIndex::<u8>::out_of_bounds(..=usize::MAX, 2);
+    }
+}
@@ -1,70 +1,433 @@
 use crate::RawVec; 
-use std::ptr;
-
-pub struct IntoIter<T> {
-    buf: RawVec<T>,
+use crate::allocator::Allocator;
+use crate::buffer::Buffer;
+use crate::growth::GrowthPolicy;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::{ptr, slice};
+
+// Owns the elements `start..end`. The buffer's length is held at 0, so
+// dropping it only frees the storage.
+pub struct IntoIter<B: Buffer> {
+    vec: B,
     start: usize,
     end: usize,
 }
 
+// Iter and IterMut only borrow the elements, so they keep a raw pointer
+// instead of the RawVec and don't need to know about the allocator
 pub struct Iter<'a, T> {
-    buf: &'a RawVec<T>,
+    ptr: *const T,
     start: usize,
-    end: usize, // Tells Rust our iterator borrows T
+    end: usize,
+    _marker: PhantomData<&'a T>, // Tells Rust our iterator borrows T
 }
 
 pub struct IterMut<'a, T> {
-    buf: &'a mut RawVec<T>,
+    ptr: *mut T,
     start: usize,
-    end: usize,  // Shows we have mutable borrow
+    end: usize,
+    _marker: PhantomData<&'a mut T>,  // Shows we have mutable borrow
 }
 
-pub struct Drain<'a, T> {
-    buf: &'a mut RawVec<T>,
+pub struct Drain<B: Buffer> {
+    vec: B,  // Length held at the drain start until drop
     start: usize,
     end: usize,
-    len: usize,
-    original_start: usize,  // Store original start position
+    tail_start: usize,  // Elements after the drained range
+    tail_len: usize,
+}
+
+// Removes a range like Drain, then fills the gap from `replace_with` when dropped
+pub struct Splice<B: Buffer, I: Iterator<Item = B::Item>> {
+    drain: Drain<B>,
+    replace_with: I,
+}
+
+// Yields the elements of a range matching `pred`, compacting the survivors as it goes
+pub struct ExtractIf<B: Buffer, F> {
+    vec: B,  // Length held at the range start until drop
+    idx: usize,  // Next element to test
+    end: usize,  // End of the range being filtered
+    del: usize,  // Elements extracted so far
+    old_len: usize,
+    pred: F,
+}
+
+// A Vec's storage seen through its parts, so the borrowing iterators can be
+// built from a RawVec and the length that goes with it
+pub struct RawParts<'a, T, A: Allocator, G: GrowthPolicy> {
+    buf: &'a mut RawVec<T, A, G>,
+    len: &'a mut usize,
+}
+
+unsafe impl<T, A: Allocator, G: GrowthPolicy> Buffer for RawParts<'_, T, A, G> {
+    type Item = T;
+
+    fn as_ptr(&self) -> *const T {
+        non_null(self.buf.ptr())
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut T {
+        non_null(self.buf.ptr())
+    }
+
+    fn len(&self) -> usize {
+        *self.len
+    }
+
+    unsafe fn set_len(&mut self, len: usize) {
+        *self.len = len;
+    }
+
+    fn reserve_total(&mut self, total: usize) {
+        let cap = self.buf.capacity();
+        if total > cap {
+            self.buf.reserve(total - cap);
+        }
+    }
+}
+
+// RawVec leaves the pointer null until the first allocation
+pub(crate) fn non_null<T>(ptr: *mut T) -> *mut T {
+    if ptr.is_null() {
+        ptr::NonNull::dangling().as_ptr()
+    } else {
+        ptr
+    }
+}
+
+// The remaining `start..end` elements as a slice. An unallocated RawVec has
+// a null pointer, which a slice can't hold, so empty ranges skip it.
+unsafe fn remaining<'a, T>(ptr: *mut T, start: usize, end: usize) -> &'a mut [T] {
+    if start == end {
+        &mut []
+    } else {
+        slice::from_raw_parts_mut(ptr.add(start), end - start)
+    }
+}
+
+// Drops `count` elements starting at `start`, for iterators that own them
+unsafe fn drop_range<T>(ptr: *mut T, start: usize, count: usize) {
+    if std::mem::needs_drop::<T>() && count > 0 {
+        ptr::drop_in_place(ptr::slice_from_raw_parts_mut(ptr.add(start), count));
+    }
+}
+
+// Result shape of the (unstable) Iterator::advance_by
+fn advance_result(requested: usize, taken: usize) -> Result<(), NonZeroUsize> {
+    NonZeroUsize::new(requested - taken).map_or(Ok(()), Err)
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> IntoIter<crate::Vec<T, A, G>> {
+    pub fn new(buf: RawVec<T, A, G>, start: usize, end: usize) -> Self {
+        Self { vec: crate::Vec { buf, len: 0 }, start, end }
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.vec.buf.allocator()
+    }
+}
+
+impl<B: Buffer> IntoIter<B> {
+    pub(crate) fn from_buffer(mut vec: B) -> Self {
+        let end = vec.len();
+        // The iterator owns the elements from here on
+        unsafe { vec.set_len(0) };
+        Self { vec, start: 0, end }
+    }
+
+    /// Returns the elements that have not been yielded yet.
+    pub fn as_slice(&self) -> &[B::Item] {
+        unsafe { remaining(self.vec.as_ptr() as *mut B::Item, self.start, self.end) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [B::Item] {
+        unsafe { remaining(self.vec.as_mut_ptr(), self.start, self.end) }
+    }
+
+    /// Skips and drops up to `n` elements from the front in O(1) moves.
+    /// Returns how many were missing if fewer than `n` remained.
+    pub fn advance_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        let step = n.min(self.end - self.start);
+        let from = self.start;
+        // Advance first so a panicking destructor can't cause a double drop
+        self.start += step;
+        unsafe { drop_range(self.vec.as_mut_ptr(), from, step) };
+        advance_result(n, step)
+    }
+
+    /// `advance_by` from the back.
+    pub fn advance_back_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        let step = n.min(self.end - self.start);
+        self.end -= step;
+        unsafe { drop_range(self.vec.as_mut_ptr(), self.end, step) };
+        advance_result(n, step)
+    }
+}
+
+impl<T, A: Allocator + Clone, G: GrowthPolicy + Clone> IntoIter<crate::Vec<T, A, G>> {
+    /// `self.map(f).collect()` that writes the results back into this
+    /// iterator's buffer instead of allocating a new one, whenever `U` has
+    /// the same alignment as `T` and its size divides `T`'s. Otherwise falls
+    /// back to a fresh allocation.
+    ///
+    /// Only these methods reuse the buffer; `collect` on a `map` adapter
+    /// still allocates.
+    pub fn map_collect<U, F: FnMut(T) -> U>(self, mut f: F) -> crate::Vec<U, A, G> {
+        self.filter_map_collect(|item| Some(f(item)))
+    }
+
+    /// In-place counterpart of `self.filter_map(f).collect()`; see `map_collect`.
+    pub fn filter_map_collect<U, F>(mut self, mut f: F) -> crate::Vec<U, A, G>
+    where
+        F: FnMut(T) -> Option<U>,
+    {
+        let (size_t, size_u) = (std::mem::size_of::<T>(), std::mem::size_of::<U>());
+        let reusable = size_t != 0
+            && size_u != 0
+            && size_t % size_u == 0
+            && std::mem::align_of::<T>() == std::mem::align_of::<U>()
+            && self.vec.capacity() > 0;
+
+        if !reusable {
+            let (policy, alloc) = (self.vec.buf.policy().clone(), self.vec.buf.allocator().clone());
+            let mut out = crate::Vec::with_policy_in(policy, alloc);
+            out.extend(self.by_ref().filter_map(f));
+            return out;
+        }
+
+        // Drops the outputs written so far if `f` panics. The IntoIter then
+        // drops the unread inputs and frees the buffer with its original layout.
+        struct Written<U> {
+            dst: *mut U,
+            len: usize,
+        }
+
+        impl<U> Drop for Written<U> {
+            fn drop(&mut self) {
+                unsafe { drop_range(self.dst, 0, self.len) };
+            }
+        }
+
+        // Output `i` never reaches past input `i`, which has already been read
+        // out, so writes only land on consumed slots
+        let mut written = Written { dst: self.vec.buf.ptr() as *mut U, len: 0 };
+        while self.start < self.end {
+            let item = self.vec.buf.read_at(self.start);
+            self.start += 1;
+            if let Some(out) = f(item) {
+                unsafe { written.dst.add(written.len).write(out) };
+                written.len += 1;
+            }
+        }
+        let len = written.len;
+        std::mem::forget(written);
+
+        // Hand the buffer over, reinterpreted as a buffer of U. The byte size
+        // is unchanged, so the allocation layout still matches.
+        let this = std::mem::ManuallyDrop::new(self);
+        let (ptr, cap, alloc, policy) = unsafe { ptr::read(&this.vec.buf) }.into_raw_parts();
+        let new_cap = cap * (size_t / size_u);
+        let buf = unsafe { RawVec::from_raw_parts_in(ptr as *mut U, new_cap, policy, alloc) };
+        crate::Vec { buf, len }
+    }
 }
 
-impl<T> IntoIter<T> {
-    pub fn new(buf: RawVec<T>, start: usize, end: usize) -> Self {
-        Self { buf, start, end }
+impl<T: Clone, A: Allocator + Clone, G: GrowthPolicy + Clone> Clone for IntoIter<crate::Vec<T, A, G>> {
+    // Clones the remaining elements into a fresh buffer
+    fn clone(&self) -> Self {
+        let policy = self.vec.buf.policy().clone();
+        let mut vec = crate::Vec::with_policy_in(policy, self.vec.buf.allocator().clone());
+        vec.extend_from_slice(self.as_slice());
+        vec.into_iter()
     }
 }
 
 impl<'a, T> Iter<'a, T> {
-    pub fn new(buf: &'a RawVec<T>, start: usize, end: usize) -> Self {
-        Self { buf, start, end }
+    pub fn new<A: Allocator, G: GrowthPolicy>(buf: &'a RawVec<T, A, G>, start: usize, end: usize) -> Self {
+        Self::from_raw(buf.ptr(), start, end)
+    }
+
+    // For containers that don't keep their elements in a RawVec (e.g. inline
+    // storage). The caller guarantees `start..end` is initialized for 'a.
+    pub(crate) fn from_raw(ptr: *const T, start: usize, end: usize) -> Self {
+        Self { ptr, start, end, _marker: PhantomData }
+    }
+
+    /// Returns the elements that have not been yielded yet.
+    pub fn as_slice(&self) -> &'a [T] {
+        unsafe { remaining(self.ptr as *mut T, self.start, self.end) }
+    }
+
+    /// Skips up to `n` elements from the front in O(1).
+    pub fn advance_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        let step = n.min(self.end - self.start);
+        self.start += step;
+        advance_result(n, step)
+    }
+
+    pub fn advance_back_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        let step = n.min(self.end - self.start);
+        self.end -= step;
+        advance_result(n, step)
+    }
+}
+
+// Manual impl: deriving would require T: Clone
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self::from_raw(self.ptr, self.start, self.end)
     }
 }
 
 impl<'a, T> IterMut<'a, T> {
-    pub fn new(buf: &'a mut RawVec<T>, start: usize, end: usize) -> Self {
-        Self { buf, start, end }
+    pub fn new<A: Allocator, G: GrowthPolicy>(buf: &'a mut RawVec<T, A, G>, start: usize, end: usize) -> Self {
+        Self::from_raw(buf.ptr(), start, end)
+    }
+
+    // Mutable counterpart of Iter::from_raw
+    pub(crate) fn from_raw(ptr: *mut T, start: usize, end: usize) -> Self {
+        Self { ptr, start, end, _marker: PhantomData }
+    }
+
+    /// Returns the elements that have not been yielded yet.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { remaining(self.ptr, self.start, self.end) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { remaining(self.ptr, self.start, self.end) }
+    }
+
+    pub fn advance_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        let step = n.min(self.end - self.start);
+        self.start += step;
+        advance_result(n, step)
+    }
+
+    pub fn advance_back_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        let step = n.min(self.end - self.start);
+        self.end -= step;
+        advance_result(n, step)
+    }
+}
+
+impl<'a, T, A: Allocator, G: GrowthPolicy> Drain<RawParts<'a, T, A, G>> {
+    pub fn new(buf: &'a mut RawVec<T, A, G>, vec_len: &'a mut usize, start: usize, end: usize) -> Self {
+        Self::from_buffer(RawParts { buf, len: vec_len }, start, end)
     }
 }
 
-impl<'a, T> Drain<'a, T> {
-    pub fn new(buf: &'a mut RawVec<T>, start: usize, end: usize, len: usize) -> Self {
-        Self { 
-            buf, 
-            start, 
-            end, 
-            len,
-            original_start: start,  // Save original start position
+impl<B: Buffer> Drain<B> {
+    pub(crate) fn from_buffer(mut vec: B, start: usize, end: usize) -> Self {
+        let tail_len = vec.len() - end;
+
+        // Hide the drained range and the tail, so leaking the Drain only
+        // leaks elements instead of exposing moved-out slots
+        unsafe { vec.set_len(start) };
+
+        Self {
+            vec,
+            start,
+            end,
+            tail_start: end,
+            tail_len,
+        }
+    }
+
+    /// Returns the elements that have not been yielded yet.
+    pub fn as_slice(&self) -> &[B::Item] {
+        unsafe { remaining(self.vec.as_ptr() as *mut B::Item, self.start, self.end) }
+    }
+
+    /// Removes and drops up to `n` elements from the front of the range.
+    pub fn advance_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        let step = n.min(self.end - self.start);
+        let from = self.start;
+        self.start += step;
+        unsafe { drop_range(self.vec.as_mut_ptr(), from, step) };
+        advance_result(n, step)
+    }
+
+    pub fn advance_back_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        let step = n.min(self.end - self.start);
+        self.end -= step;
+        unsafe { drop_range(self.vec.as_mut_ptr(), self.end, step) };
+        advance_result(n, step)
+    }
+
+    /// Stops draining and keeps the elements that were not yielded in the
+    /// vector, in their original order.
+    pub fn keep_rest(self) {
+        let mut this = std::mem::ManuallyDrop::new(self);
+
+        // Move the unyielded elements down to the drain start
+        let (start, kept) = (this.start, this.end - this.start);
+        let dest = this.vec.len();
+        unsafe {
+            let ptr = this.vec.as_mut_ptr();
+            if start != dest {
+                ptr::copy(ptr.add(start), ptr.add(dest), kept);
+            }
+            this.vec.set_len(dest + kept);
+        }
+
+        this.close_gap();
+
+        // The buffer itself may be an owned view that needs dropping
+        unsafe { ptr::drop_in_place(&mut this.vec) };
+    }
+
+    // Writes items from `replace_with` into the gap between the kept prefix
+    // and the tail. Returns false if the iterator ran out first.
+    fn fill<I: Iterator<Item = B::Item>>(&mut self, replace_with: &mut I) -> bool {
+        while self.vec.len() < self.tail_start {
+            match replace_with.next() {
+                Some(item) => unsafe {
+                    let len = self.vec.len();
+                    self.vec.as_mut_ptr().add(len).write(item);
+                    self.vec.set_len(len + 1);
+                },
+                None => return false,
+            }
+        }
+        true
+    }
+
+    // Widens the gap by `additional` slots, growing the buffer if needed
+    fn move_tail(&mut self, additional: usize) {
+        let new_tail_start = self.tail_start + additional;
+        self.vec.reserve_total(new_tail_start + self.tail_len);
+        unsafe {
+            let ptr = self.vec.as_mut_ptr();
+            ptr::copy(ptr.add(self.tail_start), ptr.add(new_tail_start), self.tail_len);
+        }
+        self.tail_start = new_tail_start;
+    }
+
+    // Moves the tail down behind the kept elements and restores the length
+    fn close_gap(&mut self) {
+        let dest = self.vec.len();
+        unsafe {
+            let ptr = self.vec.as_mut_ptr();
+            if self.tail_start != dest {
+                ptr::copy(ptr.add(self.tail_start), ptr.add(dest), self.tail_len);
+            }
+            self.vec.set_len(dest + self.tail_len);
         }
     }
 }
 
-impl<T> Iterator for IntoIter<T> {
-    type Item = T;
-    fn next(&mut self) -> Option<T> {
+impl<B: Buffer> Iterator for IntoIter<B> {
+    type Item = B::Item;
+    fn next(&mut self) -> Option<B::Item> {
         if self.start == self.end {
             None
         } else {
-            let result: T = self.buf.read_at(self.start);
-            self.start = self.start + 1;
+            let result = unsafe { self.vec.as_ptr().add(self.start).read() };
+            self.start += 1;
             Some(result)
         }
     }
@@ -72,6 +435,19 @@ impl<T> Iterator for IntoIter<T> {
     fn size_hint(&self) -> (usize, Option<usize>) {
         (self.end - self.start, Some(self.end - self.start))
     }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.advance_by(n).ok()?;
+        self.next()
+    }
+
+    fn count(self) -> usize {
+        self.len()
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
 }
 
 impl<'a, T> Iterator for Iter<'a, T> {
@@ -80,8 +456,8 @@ impl<'a, T> Iterator for Iter<'a, T> {
         if self.start == self.end {
             None
         } else {
-            let result: &T = self.buf.get_ref(self.start);
-            self.start = self.start + 1;
+            let result: &T = unsafe { &*self.ptr.add(self.start) };
+            self.start += 1;
             Some(result)
         }
     }   
@@ -89,6 +465,19 @@ impl<'a, T> Iterator for Iter<'a, T> {
     fn size_hint(&self) -> (usize, Option<usize>) {
         (self.end - self.start, Some(self.end - self.start))
     }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.advance_by(n).ok()?;
+        self.next()
+    }
+
+    fn count(self) -> usize {
+        self.len()
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
 }   
 
 impl<'a, T> Iterator for IterMut<'a, T> {
@@ -98,7 +487,7 @@ impl<'a, T> Iterator for IterMut<'a, T> {
             None    
         } else {
             unsafe {
-                let ptr: *mut T = self.buf.ptr().add(self.start);
+                let ptr: *mut T = self.ptr.add(self.start);
                 self.start += 1;
                 Some(&mut *ptr)
             }
@@ -108,16 +497,28 @@ impl<'a, T> Iterator for IterMut<'a, T> {
     fn size_hint(&self) -> (usize, Option<usize>) {
         (self.end - self.start, Some(self.end - self.start))
     }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.advance_by(n).ok()?;
+        self.next()
+    }
+
+    fn count(self) -> usize {
+        self.len()
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
 }
 
-impl<'a, T> Iterator for Drain<'a, T> {
-    type Item = T;
-    fn next(&mut self) -> Option<T> {
-        println!("Drain::next - start: {}, end: {}", self.start, self.end);
+impl<B: Buffer> Iterator for Drain<B> {
+    type Item = B::Item;
+    fn next(&mut self) -> Option<B::Item> {
         if self.start == self.end {
             None
         } else {
-            let result: T = self.buf.read_at(self.start);
+            let result = unsafe { self.vec.as_ptr().add(self.start).read() };
             self.start += 1;
             Some(result)
         }
@@ -126,50 +527,210 @@ impl<'a, T> Iterator for Drain<'a, T> {
     fn size_hint(&self) -> (usize, Option<usize>) {
         (self.end - self.start, Some(self.end - self.start))
     }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.advance_by(n).ok()?;
+        self.next()
+    }
+
+    fn count(self) -> usize {
+        self.len()
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
 }           
 
-impl<T> DoubleEndedIterator for IntoIter<T> {
-    fn next_back(&mut self) -> Option<T> {
+impl<B: Buffer, I: Iterator<Item = B::Item>> Splice<B, I> {
+    pub(crate) fn new(drain: Drain<B>, replace_with: I) -> Self {
+        Splice { drain, replace_with }
+    }
+}
+
+impl<B: Buffer, I: Iterator<Item = B::Item>> Iterator for Splice<B, I> {
+    type Item = I::Item;
+    fn next(&mut self) -> Option<I::Item> {
+        self.drain.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.drain.size_hint()
+    }
+}
+
+impl<B: Buffer, I: Iterator<Item = B::Item>> DoubleEndedIterator for Splice<B, I> {
+    fn next_back(&mut self) -> Option<I::Item> {
+        self.drain.next_back()
+    }
+}
+
+impl<B: Buffer, I: Iterator<Item = B::Item>> Drop for Splice<B, I> {
+    fn drop(&mut self) {
+        // Drop whatever the caller didn't take out of the range
+        self.drain.by_ref().for_each(drop);
+
+        // Reuse the drained slots first
+        if !self.drain.fill(&mut self.replace_with) {
+            return;
+        }
+
+        // Trust the lower bound to make room for the rest. An exact size_hint
+        // means this is the only time the tail moves.
+        let (lower, _) = self.replace_with.size_hint();
+        if lower > 0 {
+            self.drain.move_tail(lower);
+            if !self.drain.fill(&mut self.replace_with) {
+                return;
+            }
+        }
+
+        // The hint was too low: buffer the leftovers so the tail moves once more
+        let collected: crate::Vec<I::Item> = self.replace_with.by_ref().collect();
+        if !collected.is_empty() {
+            self.drain.move_tail(collected.len());
+            let filled = self.drain.fill(&mut collected.into_iter());
+            debug_assert!(filled);
+        }
+
+        // Drain's own drop closes any remaining gap and restores the length
+    }
+}
+
+impl<'a, T, F, A: Allocator, G: GrowthPolicy> ExtractIf<RawParts<'a, T, A, G>, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    pub(crate) fn new(
+        buf: &'a mut RawVec<T, A, G>,
+        vec_len: &'a mut usize,
+        start: usize,
+        end: usize,
+        pred: F,
+    ) -> Self {
+        Self::from_buffer(RawParts { buf, len: vec_len }, start, end, pred)
+    }
+}
+
+impl<B: Buffer, F> ExtractIf<B, F>
+where
+    F: FnMut(&mut B::Item) -> bool,
+{
+    pub(crate) fn from_buffer(mut vec: B, start: usize, end: usize, pred: F) -> Self {
+        let old_len = vec.len();
+
+        // The prefix is never touched, so only the range and tail are hidden
+        unsafe { vec.set_len(start) };
+
+        ExtractIf { vec, idx: start, end, del: 0, old_len, pred }
+    }
+}
+
+impl<B: Buffer, F> Iterator for ExtractIf<B, F>
+where
+    F: FnMut(&mut B::Item) -> bool,
+{
+    type Item = B::Item;
+    fn next(&mut self) -> Option<B::Item> {
+        let ptr = self.vec.as_mut_ptr();
+        while self.idx < self.end {
+            let i = self.idx;
+            let cur = unsafe { &mut *ptr.add(i) };
+
+            // Advance only after the predicate returns, so a panic leaves the
+            // current element in place to be kept
+            let extract = (self.pred)(cur);
+            self.idx += 1;
+
+            if extract {
+                self.del += 1;
+                return Some(unsafe { ptr::read(cur) });
+            } else if self.del > 0 {
+                unsafe {
+                    ptr::copy_nonoverlapping(cur, ptr.add(i - self.del), 1);
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.end - self.idx))
+    }
+}
+
+impl<B: Buffer, F> Drop for ExtractIf<B, F> {
+    fn drop(&mut self) {
+        // Close the holes: move the untested part of the range and the tail down
+        unsafe {
+            let ptr = self.vec.as_mut_ptr();
+            if self.del > 0 {
+                ptr::copy(ptr.add(self.idx), ptr.add(self.idx - self.del), self.old_len - self.idx);
+            }
+            self.vec.set_len(self.old_len - self.del);
+        }
+    }
+}
+
+impl<B: Buffer> DoubleEndedIterator for IntoIter<B> {
+    fn next_back(&mut self) -> Option<B::Item> {
         if self.start == self.end {
             None
         } else {
             self.end -= 1;
-            Some(self.buf.read_at(self.end))
+            Some(unsafe { self.vec.as_ptr().add(self.end).read() })
         }
     }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.advance_back_by(n).ok()?;
+        self.next_back()
+    }
 }
 
-impl<'a, T> DoubleEndedIterator for Drain<'a, T> {
-    fn next_back(&mut self) -> Option<T> {
-        println!("Drain::next_back - start: {}, end: {}", self.start, self.end);
+impl<B: Buffer> DoubleEndedIterator for Drain<B> {
+    fn next_back(&mut self) -> Option<B::Item> {
         if self.start == self.end {
             None
         } else {
             self.end -= 1;
-            Some(self.buf.read_at(self.end))
+            Some(unsafe { self.vec.as_ptr().add(self.end).read() })
         }
     }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.advance_back_by(n).ok()?;
+        self.next_back()
+    }
 }
 
-impl<'a, T> Drop for Drain<'a, T> {
+impl<B: Buffer> Drop for IntoIter<B> {
     fn drop(&mut self) {
-        // Drop any remaining elements in the drain range that weren't consumed
-        while self.start < self.end {
-            unsafe {
-                ptr::drop_in_place(self.buf.ptr().add(self.start));
+        // Drop the elements that were never yielded; `vec` frees the storage
+        unsafe { drop_range(self.vec.as_mut_ptr(), self.start, self.end - self.start) };
+    }
+}
+
+impl<B: Buffer> Drop for Drain<B> {
+    fn drop(&mut self) {
+        // Shifts the tail back even if a destructor below panics
+        struct CloseGapOnDrop<'r, B: Buffer>(&'r mut Drain<B>);
+
+        impl<B: Buffer> Drop for CloseGapOnDrop<'_, B> {
+            fn drop(&mut self) {
+                self.0.close_gap();
             }
-            self.start += 1;
         }
 
+        let (start, remaining) = (self.start, self.end - self.start);
+        self.start = self.end;
+        let guard = CloseGapOnDrop(self);
+
+        // Drop any remaining elements in the drain range that weren't consumed
+        unsafe { drop_range(guard.0.vec.as_mut_ptr(), start, remaining) };
+
         // Now shift any elements that were after the drain range
-        let tail_remaining = self.len - self.end;
-        if tail_remaining > 0 {
-            self.buf.shift_left(
-                self.end,      // Start of remaining elements
-                tail_remaining,    // How many elements to move
-                self.end - self.original_start  // How far to move them
-            );
-        }
+        drop(guard);
     }
 }
 
@@ -179,9 +740,14 @@ impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
             None
         } else {
             self.end -= 1;
-            Some(self.buf.get_ref(self.end))
+            Some(unsafe { &*self.ptr.add(self.end) })
         }
     }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.advance_back_by(n).ok()?;
+        self.next_back()
+    }
 }
 
 impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
@@ -191,13 +757,27 @@ impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
         } else {
             self.end -= 1;
             unsafe {
-                let ptr = self.buf.ptr().add(self.end);
+                let ptr = self.ptr.add(self.end);
                 Some(&mut *ptr)
             }
         }
     }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.advance_back_by(n).ok()?;
+        self.next_back()
+    }
 }
 
+impl<B: Buffer> ExactSizeIterator for IntoIter<B> {}
+impl<B: Buffer> FusedIterator for IntoIter<B> {}
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+impl<B: Buffer> ExactSizeIterator for Drain<B> {}
+impl<B: Buffer> FusedIterator for Drain<B> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -242,7 +822,8 @@ mod tests {
         raw.write_at(0, 1);
         raw.write_at(1, 2);
         raw.write_at(2, 3);
-        let drain = Drain::new(&mut raw, 0, 3, 3);
+        let mut len = 3;
+        let drain = Drain::new(&mut raw, &mut len, 0, 3);
         let collected: Vec<i32> = drain.collect();
         assert_eq!(collected, vec![1, 2, 3]);
     }
@@ -295,7 +876,8 @@ mod tests {
         raw.write_at(0, 1);
         raw.write_at(1, 2);
         raw.write_at(2, 3);
-        let mut drain = Drain::new(&mut raw, 0, 3, 3);
+        let mut len = 3;
+        let mut drain = Drain::new(&mut raw, &mut len, 0, 3);
         assert_eq!(drain.next(), Some(1));
         assert_eq!(drain.next_back(), Some(3));
         assert_eq!(drain.next(), Some(2));
@@ -322,12 +904,120 @@ mod tests {
         assert_eq!(iter.size_hint(), (3, Some(3)));
         
         // Test mutable iterator
-        let iter = IterMut::new(&mut raw2, 0, 3);
-        assert_eq!(iter.size_hint(), (3, Some(3)));
-        drop(iter);  // Explicitly drop to release mutable borrow
+        {
+            // Scoped to release the mutable borrow
+            let iter = IterMut::new(&mut raw2, 0, 3);
+            assert_eq!(iter.size_hint(), (3, Some(3)));
+        }
         
         // Test drain
-        let drain = Drain::new(&mut raw2, 0, 3, 3);
+        let mut len = 3;
+        let drain = Drain::new(&mut raw2, &mut len, 0, 3);
         assert_eq!(drain.size_hint(), (3, Some(3)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_drain_keep_rest() {
+        let mut raw = RawVec::with_capacity(5);
+        for i in 0..5 {
+            raw.write_at(i, i as i32);
+        }
+        let mut len = 5;
+        let mut drain = Drain::new(&mut raw, &mut len, 1, 4);
+        assert_eq!(drain.next(), Some(1));
+        assert_eq!(drain.as_slice(), &[2, 3]);
+        drain.keep_rest();
+
+        assert_eq!(len, 4);
+        let kept: Vec<i32> = (0..len).map(|i| raw.read_at(i)).collect();
+        assert_eq!(kept, vec![0, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_nth_and_advance_by() {
+        let mut raw = RawVec::with_capacity(6);
+        for i in 0..6 {
+            raw.write_at(i, i as i32);
+        }
+        let mut iter = Iter::new(&raw, 0, 6);
+        assert_eq!(iter.nth(1), Some(&1));
+        assert_eq!(iter.nth_back(1), Some(&4));
+        assert_eq!(iter.as_slice(), &[2, 3]);
+        assert_eq!(iter.advance_by(5), Err(NonZeroUsize::new(3).unwrap()));
+        assert_eq!(iter.next(), None);
+
+        let mut iter = Iter::new(&raw, 0, 6);
+        assert_eq!(iter.len(), 6);
+        assert_eq!(iter.clone().count(), 6);
+        assert_eq!(iter.clone().last(), Some(&5));
+        assert_eq!(iter.nth(6), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_into_iter_slices_and_clone() {
+        let mut raw = RawVec::with_capacity(4);
+        for i in 0..4 {
+            raw.write_at(i, i.to_string());
+        }
+        let mut iter = IntoIter::new(raw, 0, 4);
+        assert_eq!(iter.nth(1), Some("1".to_string()));
+        iter.as_mut_slice()[0].push('!');
+
+        let cloned = iter.clone();
+        assert_eq!(iter.as_slice(), &["2!", "3"]);
+        assert_eq!(cloned.collect::<Vec<_>>(), ["2!", "3"]);
+        assert_eq!(iter.last(), Some("3".to_string()));
+    }
+
+    #[test]
+    fn test_map_collect_reuses_buffer() {
+        let vec: crate::Vec<u64> = (0..100).collect();
+        let ptr = vec.as_ptr() as usize;
+        let cap = vec.capacity();
+
+        // Same size
+        let doubled = vec.into_iter().map_collect(|x| x as i64 * 2);
+        assert_eq!(doubled.as_ptr() as usize, ptr);
+        assert_eq!(doubled.capacity(), cap);
+        assert_eq!(doubled[99], 198);
+
+        // u64 -> u32 changes the alignment, so that needs a fresh buffer
+        let narrowed = doubled.into_iter().map_collect(|x| x as u32);
+        assert_ne!(narrowed.as_ptr() as usize, ptr);
+        assert_eq!(narrowed.len(), 100);
+    }
+
+    #[test]
+    fn test_filter_map_collect_in_place() {
+        let vec: crate::Vec<[u16; 2]> = (0..10u16).map(|i| [i, i]).collect();
+        let ptr = vec.as_ptr() as usize;
+
+        // [u16; 2] -> u16: same alignment, size divides, capacity doubles
+        let mut iter = vec.into_iter();
+        iter.next();
+        let evens = iter.filter_map_collect(|[a, b]| (a % 2 == 0).then_some(a + b));
+        assert_eq!(evens.as_ptr() as usize, ptr);
+        assert_eq!(evens, [4, 8, 12, 16]);
+    }
+
+    #[test]
+    fn test_map_collect_panic_drops_everything() {
+        use std::rc::Rc;
+
+        let marker = Rc::new(());
+        let vec: crate::Vec<Rc<()>> = (0..6).map(|_| marker.clone()).collect();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut calls = 0;
+            vec.into_iter().map_collect(|rc| {
+                calls += 1;
+                if calls == 4 {
+                    panic!("mapping failed");
+                }
+                rc
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(Rc::strong_count(&marker), 1);
+    }
+}
@@ -1,16 +1,61 @@
+mod allocator;
+mod array_vec;
+mod buffer;
+mod convert;
+mod error;
+mod gap_buffer;
+mod growth;
+mod index;
+mod range;
 mod raw_vec;
+mod segmented_vec;
+mod shared_vec;
+mod thin_vec;
 mod iter;
+#[cfg(feature = "serde")]
+mod serde_impls;
+mod small_vec;
+mod vec_deque;
+pub use allocator::{AllocError, Allocator, Global};
+pub use array_vec::ArrayVec;
+pub use error::{CapacityError, GetManyMutError, TryReserveError};
+pub use gap_buffer::GapBuffer;
+pub use growth::{Doubling, Exact, Fibonacci, GrowthPolicy, PageRounded, TypeSizeAware};
+pub use index::VecIndex;
+pub use segmented_vec::{SegmentedVec, StableVec};
+pub use shared_vec::{ArcVec, RcVec, RefCount, SharedVec};
+pub use small_vec::SmallVec;
+pub use thin_vec::ThinVec;
+pub use vec_deque::VecDeque;
+use buffer::Buffer;
 use raw_vec::RawVec;
 use std::ops::Index;
 use std::ops::Deref;
 use std::ops::DerefMut;
 use std::ops::IndexMut;
-use std::slice;
-use std::mem::ManuallyDrop;
+use std::ops::{Range, RangeBounds};
+use std::borrow::{Borrow, BorrowMut};
+use std::slice::{self, SliceIndex};
+use std::mem::{ManuallyDrop, MaybeUninit};
 use std::ptr;
 
-pub struct Vec<T> {
-    buf: RawVec<T>, // delegation to RawVec for memory management
+/// Creates a crate `Vec`, like `std::vec!`: `vec![]`, `vec![a, b, c]` or
+/// `vec![elem; n]` (which clones `elem`).
+#[macro_export]
+macro_rules! vec {
+    () => {
+        $crate::Vec::new()
+    };
+    ($elem:expr; $n:expr) => {
+        $crate::Vec::from_elem($elem, $n)
+    };
+    ($($x:expr),+ $(,)?) => {
+        $crate::Vec::from([$($x),+])
+    };
+}
+
+pub struct Vec<T, A: Allocator = Global, G: GrowthPolicy = TypeSizeAware> {
+    buf: RawVec<T, A, G>, // delegation to RawVec for memory management
     len: usize,
 }
 
@@ -29,6 +74,116 @@ impl<T> Vec<T> {
         }
     }
 
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_capacity_in(capacity, Global)
+    }
+
+    /// Rebuilds a vector from a pointer, length and capacity, e.g. from
+    /// `into_raw_parts` or a buffer handed over through FFI.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been allocated by the global allocator with
+    ///   `Layout::array::<T>(capacity)`, which is what this crate's `Vec` and
+    ///   `std::vec::Vec` both use. If `capacity` is 0 or `T` is zero-sized,
+    ///   `ptr` must instead be non-null and aligned (e.g. dangling).
+    /// - `length <= capacity`, and the first `length` elements are initialized.
+    /// - Ownership moves to the vector: nothing else may use or free the buffer.
+    pub unsafe fn from_raw_parts(ptr: *mut T, length: usize, capacity: usize) -> Self {
+        Self::from_raw_parts_in(ptr, length, capacity, Global)
+    }
+
+    /// Takes the vector apart into `(ptr, len, capacity)` without freeing the
+    /// buffer. `from_raw_parts` puts it back together.
+    pub fn into_raw_parts(self) -> (*mut T, usize, usize) {
+        let (ptr, len, cap, _) = self.into_raw_parts_with_alloc();
+        (ptr, len, cap)
+    }
+
+    /// Creates a vector of `len` elements whose bytes are all zero, with a
+    /// single allocation and no per-element writes.
+    ///
+    /// # Safety
+    ///
+    /// The all-zero bit pattern must be a valid value of `T` (integers,
+    /// floats, raw pointers, `Option<&T>`, ...).
+    pub unsafe fn with_len_zeroed(len: usize) -> Self {
+        let mut vec = Self::with_capacity(len);
+        if len > 0 {
+            ptr::write_bytes(vec.buf.ptr(), 0, len);
+        }
+        vec.len = len;
+        vec
+    }
+}
+
+impl<T: Clone> Vec<T> {
+    /// Creates a vector holding `n` clones of `elem`. Backs `vec![elem; n]`.
+    pub fn from_elem(elem: T, n: usize) -> Self {
+        let mut vec = if n == 0 { Vec::new() } else { Vec::with_capacity(n) };
+        vec.resize(n, elem);
+        vec
+    }
+}
+
+impl<T, G: GrowthPolicy> Vec<T, Global, G> {
+    pub fn with_policy(policy: G) -> Self {
+        Self::with_policy_in(policy, Global)
+    }
+}
+
+impl<T, A: Allocator> Vec<T, A> {
+    /// `from_raw_parts` for a buffer allocated by `alloc`.
+    ///
+    /// # Safety
+    ///
+    /// Same contract as `from_raw_parts`, with `alloc` in place of the
+    /// global allocator.
+    pub unsafe fn from_raw_parts_in(ptr: *mut T, length: usize, capacity: usize, alloc: A) -> Self {
+        Vec {
+            buf: RawVec::from_raw_parts_in(ptr, capacity, TypeSizeAware, alloc),
+            len: length,
+        }
+    }
+
+    pub fn new_in(alloc: A) -> Self {
+        Vec {
+            buf: RawVec::new_in(alloc),
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        Vec {
+            buf: RawVec::with_capacity_in(capacity, alloc),
+            len: 0,
+        }
+    }
+
+    pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, TryReserveError> {
+        Ok(Vec {
+            buf: RawVec::try_with_capacity_in(capacity, alloc)?,
+            len: 0,
+        })
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Vec<T, A, G> {
+    pub fn with_policy_in(policy: G, alloc: A) -> Self {
+        Vec {
+            buf: RawVec::with_policy_in(policy, alloc),
+            len: 0,
+        }
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.buf.allocator()
+    }
+
+    pub fn growth_policy(&self) -> &G {
+        self.buf.policy()
+    }
+
     pub fn capacity(&self) -> usize {
         self.buf.capacity()
     }
@@ -37,6 +192,61 @@ impl<T> Vec<T> {
         self.len
     }
 
+    /// `into_raw_parts` that also hands back the allocator that owns the buffer.
+    /// The growth policy is dropped.
+    pub fn into_raw_parts_with_alloc(self) -> (*mut T, usize, usize, A) {
+        let mut this = ManuallyDrop::new(self);
+        let (ptr, len) = (this.as_mut_ptr(), this.len);
+        let buf = unsafe { ptr::read(&this.buf) };
+        let (_, cap, alloc, _) = buf.into_raw_parts();
+        (ptr, len, cap, alloc)
+    }
+
+    /// Returns a pointer to the buffer. Never null: an unallocated vector
+    /// returns a dangling, well-aligned pointer.
+    pub fn as_ptr(&self) -> *const T {
+        self.as_mut_ptr_inner()
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.as_mut_ptr_inner()
+    }
+
+    fn as_mut_ptr_inner(&self) -> *mut T {
+        iter::non_null(self.buf.ptr())
+    }
+
+    /// Sets the length without touching the elements.
+    ///
+    /// # Safety
+    ///
+    /// `new_len` must be at most `capacity()`, and the elements up to
+    /// `new_len` must be initialized.
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        debug_assert!(new_len <= self.capacity());
+        self.len = new_len;
+    }
+
+    /// Returns the unused capacity as uninitialized slots. Write into them,
+    /// then call `set_len` to make them part of the vector.
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        let spare = self.capacity() - self.len;
+        unsafe {
+            slice::from_raw_parts_mut(self.as_mut_ptr().add(self.len) as *mut MaybeUninit<T>, spare)
+        }
+    }
+
+    /// Consumes the vector and returns its contents as a slice that lives
+    /// for as long as needed. The buffer is never freed.
+    pub fn leak<'a>(self) -> &'a mut [T]
+    where
+        A: 'a,
+        G: 'a,
+    {
+        let mut this = ManuallyDrop::new(self);
+        unsafe { slice::from_raw_parts_mut(this.as_mut_ptr(), this.len) }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.len == 0
     }
@@ -45,17 +255,48 @@ impl<T> Vec<T> {
         self.buf.reserve(additional);
     }
 
+    /// Fallible counterpart of `reserve`: reports capacity overflow or
+    /// allocator failure instead of panicking or aborting.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.buf.try_reserve(additional)
+    }
+
+    /// Like `try_reserve`, but grows capacity by exactly `additional`
+    /// without applying the growth strategy.
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.buf.try_reserve_exact(additional)
+    }
+
+    // Makes room for `additional` more elements past `len`, growing at most once
+    fn reserve_spare(&mut self, additional: usize) {
+        let spare = self.capacity() - self.len;
+        if additional > spare {
+            self.reserve(additional - spare);
+        }
+    }
+
     pub fn push(&mut self, item: T) {
         if self.len == self.capacity() {
-            // When at capacity, grow by 50% or at least 1
-            let additional = std::cmp::max(1, self.capacity() / 2);
-            self.reserve(additional);
+            // When at capacity, the growth policy decides how far to grow
+            self.reserve(1);
         }
         self.buf.write_at(self.len, item);
         self.len = self.len.checked_add(1)
             .expect("push: length overflow");
     }
 
+    /// Pushes `item`, returning an error (and dropping `item`) if the
+    /// vector needed to grow and the allocation failed.
+    pub fn try_push(&mut self, item: T) -> Result<(), TryReserveError> {
+        if self.len == self.capacity() {
+            self.try_reserve(1)?;
+        }
+        self.buf.write_at(self.len, item);
+        self.len = self.len.checked_add(1)
+            .expect("try_push: length overflow");
+        Ok(())
+    }
+
     pub fn pop(&mut self) -> Option<T> {
         if self.len == 0 {
             None
@@ -73,10 +314,9 @@ impl<T> Vec<T> {
             );
         }
 
-        // When at capacity, grow by 50% or at least 1
+        // When at capacity, the growth policy decides how far to grow
         if self.len == self.capacity() {
-            let additional = std::cmp::max(1, self.capacity() / 2);
-            self.reserve(additional);
+            self.reserve(1);
         }
 
         self.buf.shift_right(index, self.len - index, 1);
@@ -85,6 +325,40 @@ impl<T> Vec<T> {
             .expect("insert: length overflow");
     }
 
+    pub fn try_insert(&mut self, index: usize, item: T) -> Result<(), TryReserveError> {
+        if index > self.len {
+            panic!(
+                "insertion index (is {}) should be <= len (is {})",
+                index, self.len
+            );
+        }
+
+        if self.len == self.capacity() {
+            self.try_reserve(1)?;
+        }
+
+        self.buf.shift_right(index, self.len - index, 1);
+        self.buf.write_at(index, item);
+        self.len = self.len.checked_add(1)
+            .expect("try_insert: length overflow");
+        Ok(())
+    }
+
+    /// Fallible counterpart of `extend`. Stops at the first failed
+    /// allocation; elements pushed before the failure are kept.
+    pub fn try_extend<I: IntoIterator<Item = T>>(&mut self, iter: I) -> Result<(), TryReserveError> {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        let spare = self.capacity() - self.len;
+        if lower > spare {
+            self.try_reserve(lower - spare)?;
+        }
+
+        for item in iter {
+            self.try_push(item)?;
+        }
+        Ok(())
+    }
 
     pub fn remove(&mut self, index: usize) -> T {
         if index >= self.len {
@@ -106,49 +380,186 @@ impl<T> Vec<T> {
     }
 
     pub fn truncate(&mut self, len: usize) {
-        // Remove elements from the end until we reach the desired length
-        while self.len > len {
-            self.pop();
-        }
-        
+        self.drop_tail(len);
+
         // Only shrink if we've truncated significantly (more than 75% reduction)
         if self.len < self.capacity() / 4 {
             self.shrink_to_fit();
         }
     }
 
+    /// Resizes to `new_len`, filling new slots with values returned by `f`.
+    pub fn resize_with<F: FnMut() -> T>(&mut self, new_len: usize, mut f: F) {
+        if new_len <= self.len {
+            self.truncate(new_len);
+            return;
+        }
+
+        self.reserve_spare(new_len - self.len);
+
+        // Bump len per element so a panicking closure leaves a valid vector
+        let ptr = self.buf.ptr();
+        while self.len < new_len {
+            unsafe {
+                ptr.add(self.len).write(f());
+            }
+            self.len += 1;
+        }
+    }
+
     pub fn clear(&mut self) {
-        // Pop all elements but don't shrink capacity
-        while let Some(_) = self.pop() {}
+        // Drop all elements but don't shrink capacity
+        self.drop_tail(0);
     }
 
     pub fn iter(&self) -> iter::Iter<'_, T> {
-       iter::Iter::new(&self.buf, 0, self.len)
+        iter::Iter::from_raw(self.as_ptr(), 0, self.len)
     }
 
     pub fn iter_mut(&mut self) -> iter::IterMut<'_, T> {
         // Create iterator that can modify elements
-        iter::IterMut::new(&mut self.buf, 0, self.len)
+        iter::IterMut::from_raw(self.as_mut_ptr(), 0, self.len)
     }
 
-    pub fn drain(&mut self, range: std::ops::Range<usize>) -> iter::Drain<'_, T> {
-        // Validate range
-        assert!(range.start <= range.end);
-        assert!(range.end <= self.len);
-    
-        // Store original length
-        let orig_len = self.len;
-        
-        // Update vector length (elements will be removed)
-        self.len -= range.end - range.start;
-        
-        // Create drain iterator
-        iter::Drain::new(
-            &mut self.buf,
-            range.start,
-            range.end,
-            orig_len
-        )
+    /// Removes the elements in `range` and yields them by value. Whatever the
+    /// iterator doesn't consume is dropped when it goes out of scope.
+    ///
+    /// Panics if the start of the range is after its end, or the end is
+    /// past the length of the vector.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> iter::Drain<iter::RawParts<'_, T, A, G>> {
+        let Range { start, end } = range::slice_range(range, self.len);
+        iter::Drain::new(&mut self.buf, &mut self.len, start, end)
+    }
+
+    /// Replaces the elements in `range` with the contents of `replace_with`
+    /// and yields the removed elements. The replacement happens when the
+    /// returned `Splice` is dropped; the tail is moved only once if the
+    /// replacement reports an exact `size_hint`.
+    ///
+    /// Panics under the same conditions as `drain`.
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> iter::Splice<iter::RawParts<'_, T, A, G>, I::IntoIter>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        iter::Splice::new(self.drain(range), replace_with.into_iter())
+    }
+
+    /// Keeps only the elements for which `f` returns true, in order.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.retain_mut(|item| f(item));
+    }
+
+    /// Like `retain`, but the predicate may mutate the elements it keeps.
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
+        self.extract_if(.., |item| !f(item)).for_each(drop);
+    }
+
+    /// Removes and yields the elements in `range` for which `filter` returns
+    /// true. Elements not yet visited when the iterator is dropped are kept.
+    ///
+    /// Panics under the same conditions as `drain`.
+    pub fn extract_if<F, R>(&mut self, range: R, filter: F) -> iter::ExtractIf<iter::RawParts<'_, T, A, G>, F>
+    where
+        F: FnMut(&mut T) -> bool,
+        R: RangeBounds<usize>,
+    {
+        let Range { start, end } = range::slice_range(range, self.len);
+        iter::ExtractIf::new(&mut self.buf, &mut self.len, start, end, filter)
+    }
+
+    /// Removes consecutive elements that map to the same key.
+    pub fn dedup_by_key<F, K>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut T) -> K,
+        K: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    /// Removes consecutive elements for which `same_bucket(current, previous)`
+    /// returns true, keeping the first of each run. Compacts in place and
+    /// never reallocates.
+    pub fn dedup_by<F>(&mut self, same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        self.dedup_in_place(same_bucket);
+    }
+
+    /// Moves all elements of `other` to the end of `self` with a single copy,
+    /// leaving `other` empty but with its capacity intact.
+    pub fn append<A2: Allocator, G2: GrowthPolicy>(&mut self, other: &mut Vec<T, A2, G2>) {
+        let count = other.len;
+        if count == 0 {
+            return;
+        }
+        self.reserve_spare(count);
+        unsafe {
+            ptr::copy_nonoverlapping(other.buf.ptr(), self.buf.ptr().add(self.len), count);
+        }
+        other.len = 0;
+        self.len += count;
+    }
+
+    /// Splits off the elements from `at` onwards into a new vector that
+    /// shares this one's allocator and growth policy.
+    pub fn split_off(&mut self, at: usize) -> Self
+    where
+        A: Clone,
+        G: Clone,
+    {
+        if at > self.len {
+            panic!("`at` split index (is {}) should be <= len (is {})", at, self.len);
+        }
+
+        let count = self.len - at;
+        let mut other = Vec::with_policy_in(self.buf.policy().clone(), self.buf.allocator().clone());
+        if count > 0 {
+            other.reserve_spare(count);
+            unsafe {
+                ptr::copy_nonoverlapping(self.buf.ptr().add(at), other.buf.ptr(), count);
+            }
+        }
+        self.len = at;
+        other.len = count;
+        other
+    }
+
+    /// Returns the element or subslice at `index`, or `None` if out of bounds.
+    pub fn get<I: SliceIndex<[T]>>(&self, index: I) -> Option<&I::Output> {
+        self.as_slice().get(index)
+    }
+
+    pub fn get_mut<I: SliceIndex<[T]>>(&mut self, index: I) -> Option<&mut I::Output> {
+        self.as_mut_slice().get_mut(index)
+    }
+
+    /// Returns mutable references to several distinct elements at once.
+    /// Fails if any index is out of bounds or two indices are equal.
+    pub fn get_many_mut<const N: usize>(
+        &mut self,
+        indices: [usize; N],
+    ) -> Result<[&mut T; N], GetManyMutError> {
+        index::get_many_mut(self.as_mut_slice(), indices)
+    }
+
+    /// Returns the first `N` elements as an array, or `None` if `len < N`.
+    pub fn first_chunk<const N: usize>(&self) -> Option<&[T; N]> {
+        self.as_slice().first_chunk()
+    }
+
+    pub fn first_chunk_mut<const N: usize>(&mut self) -> Option<&mut [T; N]> {
+        self.as_mut_slice().first_chunk_mut()
+    }
+
+    /// Returns the last `N` elements as an array, or `None` if `len < N`.
+    pub fn last_chunk<const N: usize>(&self) -> Option<&[T; N]> {
+        self.as_slice().last_chunk()
+    }
+
+    pub fn last_chunk_mut<const N: usize>(&mut self) -> Option<&mut [T; N]> {
+        self.as_mut_slice().last_chunk_mut()
     }
 
     pub fn as_slice(&self) -> &[T] {
@@ -160,58 +571,144 @@ impl<T> Vec<T> {
     }
 }
 
-impl<T> Index<usize> for Vec<T> {
-    type Output = T;
+impl<T: Clone, A: Allocator, G: GrowthPolicy> Vec<T, A, G> {
+    /// Appends clones of every element in `other`, reserving once up front.
+    /// Plain `Copy` types are copied with memcpy instead of cloned one by one.
+    pub fn extend_from_slice(&mut self, other: &[T]) {
+        self.extend_cloned(other);
+    }
 
-    fn index(&self, index: usize) -> &Self::Output {
-        if index >= self.len {
-            panic!("index (is {}) should be < len (is {})", index, self.len);
+    /// Resizes to `new_len`, filling new slots with clones of `value`.
+    pub fn resize(&mut self, new_len: usize, value: T) {
+        if new_len <= self.len {
+            self.truncate(new_len);
+            return;
+        }
+
+        self.reserve_spare(new_len - self.len);
+
+        // Clone into all but the last slot, then move `value` into it. len is
+        // bumped per element so a panicking clone leaves a valid vector.
+        let ptr = self.buf.ptr();
+        while self.len < new_len - 1 {
+            unsafe {
+                ptr.add(self.len).write(value.clone());
+            }
+            self.len += 1;
+        }
+        unsafe {
+            ptr.add(self.len).write(value);
         }
-        self.buf.get_ref(index)
+        self.len += 1;
+    }
+
+    /// Appends clones of the elements in `range` to the end of the vector.
+    ///
+    /// Panics under the same conditions as `drain`.
+    pub fn extend_from_within<R: RangeBounds<usize>>(&mut self, range: R) {
+        let range = range::slice_range(range, self.len);
+        self.extend_cloned_within(range);
     }
 }
 
-impl<T> IndexMut<usize> for Vec<T> {
-    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        if index >= self.len {
-            panic!("index (is {}) should be < len (is {})", index, self.len);
+impl<T: PartialEq, A: Allocator, G: GrowthPolicy> Vec<T, A, G> {
+    /// Removes consecutive repeated elements.
+    pub fn dedup(&mut self) {
+        self.dedup_by(|a, b| a == b);
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy, I: VecIndex<T>> Index<I> for Vec<T, A, G> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        match self.as_slice().get(index.clone()) {
+            Some(output) => output,
+            None => index.out_of_bounds(self.len),
         }
+    }
+}
 
-        self.buf.get_mut(index)
+impl<T, A: Allocator, G: GrowthPolicy, I: VecIndex<T>> IndexMut<I> for Vec<T, A, G> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        let len = self.len;
+        match self.as_mut_slice().get_mut(index.clone()) {
+            Some(output) => output,
+            None => index.out_of_bounds(len),
+        }
     }
 }
 
-impl<T> Deref for Vec<T> {
+impl<T, A: Allocator, G: GrowthPolicy> Deref for Vec<T, A, G> {
     type Target = [T];
 
     fn deref(&self) -> &Self::Target {
-        unsafe { slice::from_raw_parts(self.buf.ptr(), self.len) }
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
     }
 }
 
-impl<T> DerefMut for Vec<T> {
+impl<T, A: Allocator, G: GrowthPolicy> DerefMut for Vec<T, A, G> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { slice::from_raw_parts_mut(self.buf.ptr(), self.len) }
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
     }
 }
 
-impl<T: Clone> Clone for Vec<T> {
-    fn clone(&self) -> Self {
-        Vec {
-            buf: self.buf.clone(),
-            len: self.len,
+impl<T, A: Allocator, G: GrowthPolicy> Drop for Vec<T, A, G> {
+    fn drop(&mut self) {
+        // Drop the live elements in one go; RawVec then frees the buffer
+        if std::mem::needs_drop::<T>() {
+            unsafe {
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.as_mut_ptr(), self.len));
+            }
         }
     }
 }
 
-impl<T> IntoIterator for Vec<T> {
+impl<T: Clone, A: Allocator + Clone, G: GrowthPolicy + Clone> Clone for Vec<T, A, G> {
+    fn clone(&self) -> Self {
+        // Same capacity as the original, but only the initialized prefix is cloned
+        let mut vec = Vec { buf: self.buf.clone_prefix(0), len: 0 };
+        vec.extend_from_slice(self);
+        vec
+    }
+
+    /// Reuses the existing allocation and elements where possible. Like
+    /// `extend_from_slice`, plain `Copy` types are copied with memcpy.
+    fn clone_from(&mut self, source: &Self) {
+        self.assign_cloned(source);
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> IntoIterator for Vec<T, A, G> {
     type Item = T;
-    type IntoIter = iter::IntoIter<T>;
+    type IntoIter = iter::IntoIter<Vec<T, A, G>>;
 
     fn into_iter(self) -> Self::IntoIter {
-        let vec = ManuallyDrop::new(self);
-        
-        iter::IntoIter::new(unsafe { ptr::read(&vec.buf) }, 0, vec.len)
+        iter::IntoIter::from_buffer(self)
+    }
+}
+
+unsafe impl<T, A: Allocator, G: GrowthPolicy> Buffer for Vec<T, A, G> {
+    type Item = T;
+
+    fn as_ptr(&self) -> *const T {
+        self.as_ptr()
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut T {
+        self.as_mut_ptr()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    unsafe fn set_len(&mut self, len: usize) {
+        self.set_len(len);
+    }
+
+    fn reserve_total(&mut self, total: usize) {
+        self.reserve_spare(total - self.len);
     }
 }
 
@@ -223,7 +720,7 @@ impl<T> FromIterator<T> for Vec<T> {
     }
 }
 
-impl<T> Extend<T> for Vec<T> {
+impl<T, A: Allocator, G: GrowthPolicy> Extend<T> for Vec<T, A, G> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         let iter  = iter.into_iter();
         let (lower, upper) = iter.size_hint();
@@ -235,7 +732,7 @@ impl<T> Extend<T> for Vec<T> {
     }
 }
 
-impl<T: std::fmt::Debug> std::fmt::Debug for Vec<T> {
+impl<T: std::fmt::Debug, A: Allocator, G: GrowthPolicy> std::fmt::Debug for Vec<T, A, G> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_list().entries(self.iter()).finish()
     }
@@ -247,6 +744,100 @@ impl<T> Default for Vec<T> {
     }
 }
 
+// Element-wise equality with anything that derefs to a slice, in both
+// directions, matching the set std provides for its Vec
+macro_rules! impl_slice_eq {
+    ([$($vars:tt)*] $lhs:ty, $rhs:ty) => {
+        impl<T, U, $($vars)*> PartialEq<$rhs> for $lhs
+        where
+            T: PartialEq<U>,
+        {
+            fn eq(&self, other: &$rhs) -> bool {
+                let lhs: &[T] = self.as_ref();
+                let rhs: &[U] = other.as_ref();
+                lhs == rhs
+            }
+        }
+    };
+}
+
+impl_slice_eq! { [A1: Allocator, G1: GrowthPolicy, A2: Allocator, G2: GrowthPolicy] Vec<T, A1, G1>, Vec<U, A2, G2> }
+impl_slice_eq! { [A: Allocator, G: GrowthPolicy] Vec<T, A, G>, std::vec::Vec<U> }
+impl_slice_eq! { [A: Allocator, G: GrowthPolicy] std::vec::Vec<T>, Vec<U, A, G> }
+impl_slice_eq! { [A: Allocator, G: GrowthPolicy] Vec<T, A, G>, [U] }
+impl_slice_eq! { [A: Allocator, G: GrowthPolicy] [T], Vec<U, A, G> }
+impl_slice_eq! { [A: Allocator, G: GrowthPolicy] Vec<T, A, G>, &[U] }
+impl_slice_eq! { [A: Allocator, G: GrowthPolicy] &[T], Vec<U, A, G> }
+impl_slice_eq! { [A: Allocator, G: GrowthPolicy] Vec<T, A, G>, &mut [U] }
+impl_slice_eq! { [A: Allocator, G: GrowthPolicy, const N: usize] Vec<T, A, G>, [U; N] }
+impl_slice_eq! { [A: Allocator, G: GrowthPolicy, const N: usize] [T; N], Vec<U, A, G> }
+impl_slice_eq! { [A: Allocator, G: GrowthPolicy, const N: usize] Vec<T, A, G>, &[U; N] }
+
+impl<T: Eq, A: Allocator, G: GrowthPolicy> Eq for Vec<T, A, G> {}
+
+// Lexicographic, like slices
+impl<T, A1, G1, A2, G2> PartialOrd<Vec<T, A2, G2>> for Vec<T, A1, G1>
+where
+    T: PartialOrd,
+    A1: Allocator,
+    G1: GrowthPolicy,
+    A2: Allocator,
+    G2: GrowthPolicy,
+{
+    fn partial_cmp(&self, other: &Vec<T, A2, G2>) -> Option<std::cmp::Ordering> {
+        PartialOrd::partial_cmp(&**self, &**other)
+    }
+}
+
+impl<T: Ord, A: Allocator, G: GrowthPolicy> Ord for Vec<T, A, G> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        Ord::cmp(&**self, &**other)
+    }
+}
+
+// Hashes exactly like the equivalent slice, as Borrow<[T]> requires
+impl<T: std::hash::Hash, A: Allocator, G: GrowthPolicy> std::hash::Hash for Vec<T, A, G> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::hash::Hash::hash(&**self, state)
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Borrow<[T]> for Vec<T, A, G> {
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> BorrowMut<[T]> for Vec<T, A, G> {
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> AsRef<[T]> for Vec<T, A, G> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> AsMut<[T]> for Vec<T, A, G> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> AsRef<Vec<T, A, G>> for Vec<T, A, G> {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> AsMut<Vec<T, A, G>> for Vec<T, A, G> {
+    fn as_mut(&mut self) -> &mut Self {
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -265,6 +856,14 @@ mod tests {
         assert_eq!(vec.capacity(), 10);
     }
 
+    #[test]
+    fn test_new_in_global() {
+        let mut vec: Vec<i32, Global> = Vec::new_in(Global);
+        vec.push(1);
+        assert_eq!(vec.len(), 1);
+        assert_eq!(*vec.allocator(), Global);
+    }
+
     #[test]
     fn test_push_pop() {
         let mut vec = Vec::new();
@@ -279,6 +878,59 @@ mod tests {
         assert_eq!(vec.pop(), None);
     }
 
+    #[test]
+    fn test_try_push_and_insert() {
+        let mut vec = Vec::new();
+        assert!(vec.try_push(1).is_ok());
+        assert!(vec.try_push(3).is_ok());
+        assert!(vec.try_insert(1, 2).is_ok());
+        assert_eq!(vec.as_slice(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_try_reserve_overflow() {
+        let mut vec: Vec<u64> = Vec::new();
+        vec.push(1);
+        assert_eq!(vec.try_reserve(usize::MAX), Err(TryReserveError::CapacityOverflow));
+        assert_eq!(vec.try_reserve_exact(usize::MAX / 2), Err(TryReserveError::CapacityOverflow));
+        // A failed reservation leaves the vector untouched
+        assert_eq!(vec.len(), 1);
+        assert_eq!(vec[0], 1);
+    }
+
+    #[test]
+    fn test_try_reserve_exact() {
+        let mut vec: Vec<u8> = Vec::with_capacity(10);
+        vec.try_reserve_exact(3).unwrap();
+        assert_eq!(vec.capacity(), 13);
+    }
+
+    #[test]
+    fn test_try_with_capacity() {
+        let vec: Vec<u32> = Vec::try_with_capacity(32).unwrap();
+        assert_eq!(vec.capacity(), 32);
+        assert!(matches!(
+            Vec::<u32>::try_with_capacity(usize::MAX),
+            Err(TryReserveError::CapacityOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_try_extend() {
+        let mut vec = Vec::new();
+        vec.try_extend(0..20).unwrap();
+        assert_eq!(vec.len(), 20);
+        assert_eq!(vec[19], 19);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn test_reserve_overflow_panics() {
+        let mut vec: Vec<u8> = Vec::new();
+        vec.push(1);
+        vec.reserve(usize::MAX);
+    }
+
     #[test]
     fn test_insert_remove() {
         let mut vec = Vec::new();
@@ -419,6 +1071,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_growth_policy_type_parameter() {
+        let mut vec: Vec<i32, Global, Exact> = Vec::with_policy(Exact);
+        for i in 0..5 {
+            vec.push(i);
+            assert_eq!(vec.capacity(), vec.len());
+        }
+        vec.insert(0, -1);
+        assert_eq!(vec.capacity(), 6);
+        assert_eq!(vec.as_slice(), &[-1, 0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_growth_policy_per_instance() {
+        let policy: Box<dyn GrowthPolicy> = Box::new(Fibonacci);
+        let mut vec = Vec::with_policy(policy);
+        let mut caps = std::vec::Vec::new();
+        for i in 0..100 {
+            vec.push(i);
+            if caps.last() != Some(&vec.capacity()) {
+                caps.push(vec.capacity());
+            }
+        }
+        assert_eq!(caps, [8, 13, 21, 34, 55, 89, 144]);
+    }
+
     #[test]
     fn test_truncate_with_shrink() {
         let mut vec = Vec::new();
@@ -463,4 +1141,574 @@ mod tests {
         }
         assert_eq!(vec.capacity(), cap_after_first_use);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_drain_range_bounds() {
+        let mut vec: Vec<i32> = (0..10).collect();
+
+        assert_eq!(vec.drain(..2).collect::<std::vec::Vec<_>>(), [0, 1]);
+        assert_eq!(vec.drain(6..).collect::<std::vec::Vec<_>>(), [8, 9]);
+        assert_eq!(vec.drain(1..=2).collect::<std::vec::Vec<_>>(), [3, 4]);
+        assert_eq!(vec.as_slice(), &[2, 5, 6, 7]);
+
+        assert_eq!(vec.drain(..).count(), 4);
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index 6 out of range for slice of length 5")]
+    fn test_drain_end_out_of_bounds() {
+        let mut vec: Vec<i32> = (0..5).collect();
+        vec.drain(2..=5);
+    }
+
+    #[test]
+    fn test_drain_keep_rest() {
+        let mut vec: Vec<i32> = (0..6).collect();
+        let mut drain = vec.drain(1..5);
+        assert_eq!(drain.next(), Some(1));
+        assert_eq!(drain.next_back(), Some(4));
+        assert_eq!(drain.as_slice(), &[2, 3]);
+        drain.keep_rest();
+
+        assert_eq!(vec.as_slice(), &[0, 2, 3, 5]);
+    }
+
+    #[test]
+    fn test_leaked_drain_hides_range() {
+        let mut vec: Vec<i32> = (0..6).collect();
+        std::mem::forget(vec.drain(2..4));
+
+        // Only the prefix before the drain stays visible
+        assert_eq!(vec.as_slice(), &[0, 1]);
+    }
+
+    #[test]
+    fn test_splice() {
+        let mut vec: Vec<i32> = (0..6).collect();
+
+        // Longer replacement with an exact size_hint
+        let removed: std::vec::Vec<_> = vec.splice(1..3, [10, 11, 12, 13]).collect();
+        assert_eq!(removed, [1, 2]);
+        assert_eq!(vec.as_slice(), &[0, 10, 11, 12, 13, 3, 4, 5]);
+
+        // Shorter replacement, removed elements left unconsumed
+        vec.splice(1..5, [20]);
+        assert_eq!(vec.as_slice(), &[0, 20, 3, 4, 5]);
+
+        // Replacement whose size_hint lower bound is zero
+        vec.splice(..1, (30..40).filter(|x| x % 3 == 0));
+        assert_eq!(vec.as_slice(), &[30, 33, 36, 39, 20, 3, 4, 5]);
+
+        // Empty range inserts, range at the end appends
+        vec.splice(2..2, [1, 2]);
+        vec.splice(10.., [99]);
+        assert_eq!(vec.as_slice(), &[30, 33, 1, 2, 36, 39, 20, 3, 4, 5, 99]);
+    }
+
+    #[test]
+    fn test_splice_into_empty() {
+        let mut vec: Vec<String> = Vec::new();
+        vec.splice(.., ["a".to_string(), "b".to_string()]);
+        assert_eq!(vec.len(), 2);
+        assert_eq!(vec[1], "b");
+    }
+
+    #[test]
+    fn test_splice_lying_size_hint() {
+        // Claims more items than it yields; the gap must still be closed
+        struct Liar(std::ops::Range<i32>);
+        impl Iterator for Liar {
+            type Item = i32;
+            fn next(&mut self) -> Option<i32> {
+                self.0.next()
+            }
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (10, None)
+            }
+        }
+
+        let mut vec: Vec<i32> = (0..4).collect();
+        vec.splice(1..2, Liar(7..9));
+        assert_eq!(vec.as_slice(), &[0, 7, 8, 2, 3]);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut vec: Vec<i32> = (0..10).collect();
+        vec.retain(|&x| x % 3 != 0);
+        assert_eq!(vec.as_slice(), &[1, 2, 4, 5, 7, 8]);
+
+        vec.retain_mut(|x| {
+            *x *= 10;
+            *x > 20
+        });
+        assert_eq!(vec.as_slice(), &[40, 50, 70, 80]);
+
+        vec.retain(|_| false);
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn test_extract_if() {
+        let mut vec: Vec<i32> = (0..10).collect();
+        let evens: std::vec::Vec<_> = vec.extract_if(2..8, |x| *x % 2 == 0).collect();
+        assert_eq!(evens, [2, 4, 6]);
+        assert_eq!(vec.as_slice(), &[0, 1, 3, 5, 7, 8, 9]);
+
+        // Stopping early keeps the elements that weren't visited
+        let mut iter = vec.extract_if(.., |x| *x > 2);
+        assert_eq!(iter.next(), Some(3));
+        drop(iter);
+        assert_eq!(vec.as_slice(), &[0, 1, 5, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_retain_panic_safety() {
+        use std::cell::Cell;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        struct Counted<'a>(i32, &'a Cell<usize>);
+        impl Drop for Counted<'_> {
+            fn drop(&mut self) {
+                self.1.set(self.1.get() + 1);
+            }
+        }
+
+        let drops = Cell::new(0);
+        let mut vec: Vec<Counted> = (0..8).map(|i| Counted(i, &drops)).collect();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            vec.retain(|c| {
+                if c.0 == 5 {
+                    panic!("predicate failed");
+                }
+                c.0 % 2 == 0
+            })
+        }));
+        assert!(result.is_err());
+
+        // 1 and 3 were removed and dropped exactly once; the rest survive in order
+        assert_eq!(drops.get(), 2);
+        let values: std::vec::Vec<i32> = vec.iter().map(|c| c.0).collect();
+        assert_eq!(values, [0, 2, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut vec: Vec<i32> = [1, 1, 2, 3, 3, 3, 1, 4, 4].into_iter().collect();
+        let cap = vec.capacity();
+        vec.dedup();
+        assert_eq!(vec.as_slice(), &[1, 2, 3, 1, 4]);
+        assert_eq!(vec.capacity(), cap);
+
+        let mut vec: Vec<i32> = [10, 11, 20, 25, 31, 42].into_iter().collect();
+        vec.dedup_by_key(|x| *x / 10);
+        assert_eq!(vec.as_slice(), &[10, 20, 31, 42]);
+
+        let mut vec: Vec<&str> = ["foo", "FOO", "bar", "Bar", "baz"].into_iter().collect();
+        vec.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        assert_eq!(vec.as_slice(), &["foo", "bar", "baz"]);
+    }
+
+    #[test]
+    fn test_dedup_panic_safety() {
+        use std::cell::Cell;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        struct Counted<'a>(i32, &'a Cell<usize>);
+        impl Drop for Counted<'_> {
+            fn drop(&mut self) {
+                self.1.set(self.1.get() + 1);
+            }
+        }
+
+        let drops = Cell::new(0);
+        let mut vec: Vec<Counted> = [1, 1, 2, 2, 3, 3, 4]
+            .into_iter()
+            .map(|i| Counted(i, &drops))
+            .collect();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            vec.dedup_by(|a, b| {
+                if a.0 == 3 {
+                    panic!("comparator failed");
+                }
+                a.0 == b.0
+            })
+        }));
+        assert!(result.is_err());
+
+        // The duplicate 1 and 2 are gone; everything from the panic on is kept
+        assert_eq!(drops.get(), 2);
+        let values: std::vec::Vec<i32> = vec.iter().map(|c| c.0).collect();
+        assert_eq!(values, [1, 2, 3, 3, 4]);
+    }
+
+    #[test]
+    fn test_append() {
+        let mut a: Vec<i32> = (0..3).collect();
+        let mut b: Vec<i32> = (3..40).collect();
+        let b_cap = b.capacity();
+
+        a.append(&mut b);
+        assert_eq!(a.as_slice(), (0..40).collect::<std::vec::Vec<_>>().as_slice());
+        assert!(b.is_empty());
+        assert_eq!(b.capacity(), b_cap);
+
+        // Appending an empty vector is a no-op
+        a.append(&mut b);
+        assert_eq!(a.len(), 40);
+    }
+
+    #[test]
+    fn test_split_off() {
+        let mut vec: Vec<String> = (0..5).map(|i| i.to_string()).collect();
+        let tail = vec.split_off(2);
+        assert_eq!(vec.as_slice(), &["0", "1"]);
+        assert_eq!(tail.as_slice(), &["2", "3", "4"]);
+
+        let empty = vec.split_off(2);
+        assert!(empty.is_empty());
+        assert_eq!(vec.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "`at` split index (is 4) should be <= len (is 3)")]
+    fn test_split_off_out_of_bounds() {
+        let mut vec: Vec<i32> = (0..3).collect();
+        vec.split_off(4);
+    }
+
+    #[test]
+    fn test_extend_from_within() {
+        let mut vec: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        vec.extend_from_within(1..);
+        vec.extend_from_within(..=0);
+        assert_eq!(vec.as_slice(), &["a", "b", "c", "b", "c", "a"]);
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut vec: Vec<String> = Vec::new();
+        vec.resize(3, "x".to_string());
+        assert_eq!(vec.as_slice(), &["x", "x", "x"]);
+
+        vec.resize(1, "y".to_string());
+        assert_eq!(vec.as_slice(), &["x"]);
+
+        let mut counter = 0;
+        vec.resize_with(4, || {
+            counter += 1;
+            counter.to_string()
+        });
+        assert_eq!(vec.as_slice(), &["x", "1", "2", "3"]);
+    }
+
+    #[test]
+    fn test_resize_panic_leaves_valid_vec() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut vec: Vec<String> = Vec::new();
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            vec.resize_with(10, || {
+                calls += 1;
+                if calls == 4 {
+                    panic!("generator failed");
+                }
+                calls.to_string()
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(vec.as_slice(), &["1", "2", "3"]);
+    }
+
+    #[test]
+    fn test_vec_macro_and_from_elem() {
+        let empty: Vec<i32> = crate::vec![];
+        assert!(empty.is_empty());
+
+        let listed = crate::vec![1, 2, 3];
+        assert_eq!(listed.as_slice(), &[1, 2, 3]);
+        assert_eq!(listed.capacity(), 3);
+
+        let repeated = crate::vec![String::from("ab"); 4];
+        assert_eq!(repeated.len(), 4);
+        assert!(repeated.iter().all(|s| s == "ab"));
+
+        let none: Vec<u8> = Vec::from_elem(7, 0);
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn test_with_len_zeroed() {
+        let vec: Vec<u64> = unsafe { Vec::with_len_zeroed(100) };
+        assert_eq!(vec.len(), 100);
+        assert!(vec.iter().all(|&x| x == 0));
+
+        let empty: Vec<u64> = unsafe { Vec::with_len_zeroed(0) };
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_clone_only_initialized_prefix() {
+        let mut original: Vec<String> = Vec::with_capacity(16);
+        original.push("a".to_string());
+        original.push("b".to_string());
+
+        let cloned = original.clone();
+        assert_eq!(cloned.as_slice(), &["a", "b"]);
+        assert_eq!(cloned.capacity(), original.capacity());
+    }
+
+    #[test]
+    fn test_extend_from_slice() {
+        let mut vec: Vec<String> = Vec::new();
+        vec.extend_from_slice(&["x".to_string(), "y".to_string()]);
+        vec.extend_from_slice(&[]);
+        assert_eq!(vec.as_slice(), &["x", "y"]);
+
+        let mut bytes: Vec<u8> = Vec::new();
+        bytes.extend_from_slice(b"hello ");
+        bytes.extend_from_slice(b"world");
+        assert_eq!(bytes.as_slice(), b"hello world");
+    }
+
+    #[test]
+    fn test_memcpy_path_still_calls_clone() {
+        use std::cell::Cell;
+
+        // No drop glue, so it takes the memcpy path, but its clone isn't a copy
+        #[derive(Debug, PartialEq)]
+        struct Counted(u8);
+
+        thread_local!(static CLONES: Cell<usize> = const { Cell::new(0) });
+
+        impl Clone for Counted {
+            fn clone(&self) -> Self {
+                CLONES.with(|c| c.set(c.get() + 1));
+                Counted(self.0 + 1)
+            }
+        }
+
+        let mut vec: Vec<Counted> = Vec::new();
+        vec.extend_from_slice(&[Counted(1), Counted(2)]);
+        assert_eq!(vec.as_slice(), &[Counted(2), Counted(3)]);
+        let cloned = vec.clone();
+        assert_eq!(cloned.as_slice(), &[Counted(3), Counted(4)]);
+        assert_eq!(CLONES.with(Cell::get), 4);
+    }
+
+    #[test]
+    fn test_clone_from() {
+        let source: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+
+        let mut longer: Vec<String> = (0..5).map(|i| i.to_string()).collect();
+        longer.clone_from(&source);
+        assert_eq!(longer.as_slice(), source.as_slice());
+
+        let mut shorter: Vec<String> = Vec::new();
+        shorter.push("z".to_string());
+        shorter.clone_from(&source);
+        assert_eq!(shorter.as_slice(), source.as_slice());
+
+        let mut floats: Vec<f32> = Vec::with_capacity(64);
+        let cap = floats.capacity();
+        floats.extend_from_slice(&[9.0; 10]);
+        floats.clone_from(&Vec::from([1.0, 2.0, 3.0]));
+        assert_eq!(floats.as_slice(), &[1.0, 2.0, 3.0]);
+        assert_eq!(floats.capacity(), cap);
+    }
+
+    #[test]
+    fn test_raw_parts_round_trip() {
+        let mut vec: Vec<String> = Vec::with_capacity(10);
+        vec.push("a".to_string());
+        vec.push("b".to_string());
+        let cap = vec.capacity();
+
+        let (ptr, len, capacity) = vec.into_raw_parts();
+        assert_eq!((len, capacity), (2, cap));
+
+        let rebuilt = unsafe { Vec::from_raw_parts(ptr, len, capacity) };
+        assert_eq!(rebuilt.as_slice(), &["a", "b"]);
+        assert_eq!(rebuilt.capacity(), cap);
+    }
+
+    #[test]
+    fn test_unallocated_raw_parts() {
+        let mut vec: Vec<u64> = Vec::new();
+        assert!(!vec.as_ptr().is_null());
+        assert!(vec.spare_capacity_mut().is_empty());
+
+        let (ptr, len, cap) = vec.into_raw_parts();
+        let mut rebuilt = unsafe { Vec::from_raw_parts(ptr, len, cap) };
+        rebuilt.push(7);
+        assert_eq!(rebuilt.as_slice(), &[7]);
+    }
+
+    #[test]
+    fn test_spare_capacity_and_set_len() {
+        let mut vec: Vec<u32> = Vec::with_capacity(8);
+        vec.push(1);
+
+        let spare = vec.spare_capacity_mut();
+        assert_eq!(spare.len(), 7);
+        spare[0].write(2);
+        spare[1].write(3);
+        unsafe { vec.set_len(3) };
+
+        assert_eq!(vec.as_slice(), &[1, 2, 3]);
+        assert_eq!(unsafe { *vec.as_ptr().add(2) }, 3);
+    }
+
+    #[test]
+    fn test_leak() {
+        let vec: Vec<i32> = (0..4).collect();
+        let leaked: &'static mut [i32] = vec.leak();
+        leaked[0] = 10;
+        assert_eq!(leaked, &[10, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_equality() {
+        let vec: Vec<i32> = crate::vec![1, 2, 3];
+        let other: Vec<i32> = (1..4).collect();
+        assert_eq!(vec, other);
+        assert_eq!(vec, [1, 2, 3]);
+        assert_eq!(vec, &[1, 2, 3][..]);
+        assert_eq!(vec, std::vec![1, 2, 3]);
+        assert_eq!(std::vec![1, 2, 3], vec);
+        assert_eq!([1, 2, 3], vec);
+        assert_ne!(vec, [1, 2]);
+
+        // Different growth policies still compare by contents
+        let mut exact: Vec<i32, Global, Exact> = Vec::with_policy(Exact);
+        exact.extend(1..4);
+        assert_eq!(vec, exact);
+    }
+
+    #[test]
+    fn test_ordering() {
+        let a: Vec<i32> = crate::vec![1, 2, 3];
+        let b: Vec<i32> = crate::vec![1, 3];
+        let c: Vec<i32> = crate::vec![1, 2];
+        assert!(a < b);
+        assert!(c < a);
+        assert_eq!(a.cmp(&a.clone()), std::cmp::Ordering::Equal);
+
+        let mut sorted: std::vec::Vec<Vec<i32>> = std::vec![b.clone(), a.clone(), c.clone()];
+        sorted.sort();
+        assert_eq!(sorted, [c, a, b]);
+    }
+
+    #[test]
+    fn test_hash_matches_slice() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::collections::HashMap;
+        use std::hash::{Hash, Hasher};
+
+        fn hash_of<H: Hash + ?Sized>(value: &H) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let vec: Vec<u8> = crate::vec![1, 2, 3];
+        assert_eq!(hash_of(&vec), hash_of(&[1u8, 2, 3][..]));
+        assert_eq!(hash_of(&vec), hash_of(&std::vec![1u8, 2, 3]));
+
+        // Borrow<[T]> lets a map keyed by Vec be queried with a slice
+        let mut map = HashMap::new();
+        map.insert(vec, "found");
+        assert_eq!(map.get(&[1u8, 2, 3][..]), Some(&"found"));
+    }
+
+    #[test]
+    fn test_as_ref_and_as_mut() {
+        fn sum(values: impl AsRef<[i32]>) -> i32 {
+            values.as_ref().iter().sum()
+        }
+
+        let mut vec: Vec<i32> = crate::vec![1, 2, 3];
+        assert_eq!(sum(&vec), 6);
+        let slice: &mut [i32] = vec.as_mut();
+        slice[0] = 10;
+        assert_eq!(sum(vec), 15);
+    }
+
+    #[test]
+    fn test_range_indexing() {
+        let mut vec: Vec<i32> = (0..6).collect();
+        assert_eq!(&vec[1..3], &[1, 2]);
+        assert_eq!(&vec[..=1], &[0, 1]);
+        assert_eq!(&vec[4..], &[4, 5]);
+        assert_eq!(vec[..].len(), 6);
+
+        vec[2..4].copy_from_slice(&[20, 30]);
+        assert_eq!(vec, [0, 1, 20, 30, 4, 5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index (is 7) should be <= len (is 6)")]
+    fn test_range_index_out_of_bounds() {
+        let vec: Vec<i32> = (0..6).collect();
+        let _ = &vec[2..7];
+    }
+
+    #[test]
+    #[should_panic(expected = "range start index (is 5) should be <= len (is 3)")]
+    fn test_range_from_index_past_len() {
+        let vec: Vec<i32> = (0..3).collect();
+        let _ = &vec[5..];
+    }
+
+    #[test]
+    #[should_panic(expected = "range start index (is 4) should be <= range end index (is 2)")]
+    fn test_range_index_reversed() {
+        let mut vec: Vec<i32> = (0..6).collect();
+        let (start, end) = (4, 2);
+        vec[start..end].fill(0);
+    }
+
+    #[test]
+    fn test_get_and_get_mut() {
+        let mut vec: Vec<i32> = (0..4).collect();
+        assert_eq!(vec.get(1), Some(&1));
+        assert_eq!(vec.get(4), None);
+        assert_eq!(vec.get(1..3), Some(&[1, 2][..]));
+        assert_eq!(vec.get(3..5), None);
+
+        *vec.get_mut(0).unwrap() = 10;
+        vec.get_mut(2..).unwrap().fill(7);
+        assert_eq!(vec, [10, 1, 7, 7]);
+    }
+
+    #[test]
+    fn test_get_many_mut() {
+        let mut vec: Vec<i32> = (0..5).collect();
+        let [a, b] = vec.get_many_mut([4, 0]).unwrap();
+        std::mem::swap(a, b);
+        assert_eq!(vec, [4, 1, 2, 3, 0]);
+
+        assert_eq!(
+            vec.get_many_mut([1, 5]).unwrap_err(),
+            GetManyMutError::IndexOutOfBounds { index: 5, len: 5 }
+        );
+        assert_eq!(
+            vec.get_many_mut([2, 3, 2]).unwrap_err(),
+            GetManyMutError::OverlappingIndices { index: 2 }
+        );
+    }
+
+    #[test]
+    fn test_chunks() {
+        let mut vec: Vec<i32> = (0..5).collect();
+        assert_eq!(vec.first_chunk::<2>(), Some(&[0, 1]));
+        assert_eq!(vec.last_chunk::<3>(), Some(&[2, 3, 4]));
+        assert_eq!(vec.first_chunk::<6>(), None);
+
+        vec.first_chunk_mut::<1>().unwrap()[0] = 9;
+        vec.last_chunk_mut::<1>().unwrap()[0] = 8;
+        assert_eq!(vec, [9, 1, 2, 3, 8]);
+    }
+}
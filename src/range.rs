@@ -0,0 +1,71 @@
+use std::ops::{Bound, Range, RangeBounds};
+
+// Resolves any RangeBounds against `len`, panicking with the same messages
+// as slice indexing in std
+pub(crate) fn slice_range<R: RangeBounds<usize>>(range: R, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start
+            .checked_add(1)
+            .unwrap_or_else(|| panic!("attempted to index slice from after maximum usize")),
+        Bound::Unbounded => 0,
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end
+            .checked_add(1)
+            .unwrap_or_else(|| panic!("attempted to index slice up to maximum usize")),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    if start > end {
+        panic!("slice index starts at {} but ends at {}", start, end);
+    }
+    if end > len {
+        panic!("range end index {} out of range for slice of length {}", end, len);
+    }
+
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_range_kinds() {
+        assert_eq!(slice_range(.., 5), 0..5);
+        assert_eq!(slice_range(2.., 5), 2..5);
+        assert_eq!(slice_range(..3, 5), 0..3);
+        assert_eq!(slice_range(..=3, 5), 0..4);
+        assert_eq!(slice_range(1..4, 5), 1..4);
+        assert_eq!(slice_range(1..=4, 5), 1..5);
+        assert_eq!(slice_range((Bound::Excluded(1), Bound::Unbounded), 5), 2..5);
+    }
+
+    #[test]
+    #[should_panic(expected = "slice index starts at 3 but ends at 2")]
+    fn test_start_after_end() {
+        #[allow(clippy::reversed_empty_ranges)]
+        slice_range(3..2, 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index 6 out of range for slice of length 5")]
+    fn test_end_out_of_bounds() {
+        slice_range(..=5, 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempted to index slice up to maximum usize")]
+    fn test_inclusive_end_overflow() {
+        slice_range(..=usize::MAX, 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempted to index slice from after maximum usize")]
+    fn test_exclusive_start_overflow() {
+        slice_range((Bound::Excluded(usize::MAX), Bound::Unbounded), 5);
+    }
+}
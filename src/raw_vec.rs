@@ -1,17 +1,42 @@
 use std::alloc::{self, Layout};
-use std::ptr;
+use std::mem::ManuallyDrop;
+use std::ptr::{self, NonNull};
+use crate::allocator::{Allocator, Global};
+use crate::error::TryReserveError;
+use crate::growth::{GrowthPolicy, TypeSizeAware};
 
-pub struct RawVec<T> {
+pub struct RawVec<T, A: Allocator = Global, G: GrowthPolicy = TypeSizeAware> {
     ptr: *mut T,      // Raw pointer to heap memory
     cap: usize,       // Total allocated capacity
+    alloc: A,         // Allocator that owns the buffer
+    policy: G,        // Decides how much to grow and when to shrink
 }
 
 impl<T> RawVec<T> {
-    // Growth strategy constants
-    const MIN_NON_ZERO_CAP: usize = 8;  // Minimum non-zero capacity
-    const SHRINK_THRESHOLD: f64 = 0.25;  // Shrink when waste > 25%
-    const MAX_EXCESS_CAPACITY: usize = 1024 * 1024;  // 1MB worth of elements
-    
+    pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+}
+
+impl<T, A: Allocator> RawVec<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_policy_in(TypeSizeAware, alloc)
+    }
+
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        handle_reserve(Self::try_with_capacity_in(capacity, alloc))
+    }
+
+    pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, TryReserveError> {
+        Self::try_with_capacity_policy_in(capacity, TypeSizeAware, alloc)
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> RawVec<T, A, G> {
     // Platform-specific constants
     #[cfg(target_pointer_width = "64")]
     const MAX_CAPACITY: usize = 1 << 48;  // 256 TB on 64-bit
@@ -19,222 +44,183 @@ impl<T> RawVec<T> {
     #[cfg(target_pointer_width = "32")]
     const MAX_CAPACITY: usize = 1 << 30;  // 1 GB on 32-bit
 
-    fn calculate_growth(&self, required_cap: usize) -> usize {
-        let elem_size = std::mem::size_of::<T>();
-        
-        // Calculate minimum growth based on element size
-        let min_growth = if elem_size > 1024 {
-            // Large elements (>1KB): grow by 25%
-            let growth = self.cap.saturating_add(self.cap / 4);
-            // Never round up for large types to ensure we stay under 50%
-            std::cmp::min(growth, required_cap)
-        } else if elem_size > 128 {
-            // Medium elements (>128B): grow by 50%
-            let growth = self.cap.saturating_add(self.cap / 2);
-            // Never round up for medium types to ensure we stay under 2x
-            std::cmp::min(growth, required_cap)
-        } else {
-            // Small elements: grow by 100%
-            self.cap.saturating_mul(2)
-        };
-
-        // For small types only, use the larger of minimum growth or required capacity
-        let mut new_cap = if elem_size <= 128 {
-            std::cmp::max(min_growth, required_cap)
-        } else {
-            min_growth
-        };
-
-        // Only round to power of 2 for small types
-        if elem_size <= 128 {
-            let next_pow2 = new_cap.next_power_of_two();
-            let waste = next_pow2.saturating_sub(new_cap);
-            if next_pow2 <= new_cap.saturating_add(new_cap / 8) && // Within 12.5%
-               waste * elem_size <= 16 * 1024 {  // Waste <= 16KB
-                new_cap = next_pow2;
-            }
-        }
-
-        // Ensure we don't exceed platform capacity
-        std::cmp::min(new_cap, Self::MAX_CAPACITY)
-    }
-
-    pub fn new() -> Self {
+    pub fn with_policy_in(policy: G, alloc: A) -> Self {
         // For zero-sized types, use dangling pointer and cap 0
         if std::mem::size_of::<T>() == 0 {
             RawVec {
                 ptr: std::ptr::NonNull::dangling().as_ptr(),
                 cap: 0,
+                alloc,
+                policy,
             }
         } else {
             RawVec {
                 ptr: ptr::null_mut(),
                 cap: 0,
+                alloc,
+                policy,
             }
         }
     }
 
-    pub fn with_capacity(capacity: usize) -> Self {
+    pub fn try_with_capacity_policy_in(
+        capacity: usize,
+        policy: G,
+        alloc: A,
+    ) -> Result<Self, TryReserveError> {
+        let elem_size = std::mem::size_of::<T>();
+
         // Handle zero-sized types
-        if std::mem::size_of::<T>() == 0 {
-            return RawVec {
+        if elem_size == 0 {
+            return Ok(RawVec {
                 ptr: std::ptr::NonNull::dangling().as_ptr(),
                 cap: usize::MAX, // ZSTs always have maximum capacity
-            };
+                alloc,
+                policy,
+            });
         }
 
-        // Only use MIN_NON_ZERO_CAP for zero capacity
+        // Only use the policy's minimum for zero capacity
         let capacity = if capacity == 0 {
-            Self::MIN_NON_ZERO_CAP
+            std::cmp::max(policy.min_capacity(elem_size), 1)
         } else {
             capacity
         };
 
-        // Add debug assertion for reasonable capacity
-        debug_assert!(capacity <= isize::MAX as usize, 
-            "capacity overflow: requested {} but max is {}", 
-            capacity, isize::MAX);
+        if capacity > Self::max_elements() {
+            return Err(TryReserveError::CapacityOverflow);
+        }
 
         let layout = Layout::array::<T>(capacity)
-            .expect("Failed to create layout for allocation");
-        
-        // Add debug assertion for layout size
-        debug_assert!(layout.size() <= isize::MAX as usize,
-            "allocation too large");
+            .map_err(|_| TryReserveError::CapacityOverflow)?;
 
-        let ptr = unsafe { alloc::alloc(layout) as *mut T };
-        if ptr.is_null() {
-            alloc::handle_alloc_error(layout);
-        }
-        RawVec { ptr, cap: capacity }
+        let ptr = alloc.allocate(layout)
+            .map_err(|_| TryReserveError::AllocError { layout })?;
+        Ok(RawVec { ptr: ptr.as_ptr() as *mut T, cap: capacity, alloc, policy })
+    }
+
+    // Adopts a buffer allocated by `alloc` with `Layout::array::<T>(cap)`.
+    // The caller guarantees the pointer and capacity match that allocation.
+    pub unsafe fn from_raw_parts_in(ptr: *mut T, cap: usize, policy: G, alloc: A) -> Self {
+        // ZSTs never allocate, so whatever capacity was reported is irrelevant
+        let cap = if std::mem::size_of::<T>() == 0 { usize::MAX } else { cap };
+        RawVec { ptr, cap, alloc, policy }
+    }
+
+    // Gives up ownership of the buffer without freeing it
+    pub fn into_raw_parts(self) -> (*mut T, usize, A, G) {
+        let this = ManuallyDrop::new(self);
+        unsafe { (this.ptr, this.cap, ptr::read(&this.alloc), ptr::read(&this.policy)) }
     }
 
     pub fn capacity(&self) -> usize {
         self.cap
     }
 
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    pub fn policy(&self) -> &G {
+        &self.policy
+    }
+
+    // Largest capacity whose layout still fits in isize::MAX bytes
+    fn max_elements() -> usize {
+        std::cmp::min(
+            isize::MAX as usize / std::mem::size_of::<T>().max(1),
+            Self::MAX_CAPACITY
+        )
+    }
+
     pub fn reserve(&mut self, additional: usize) {
+        handle_reserve(self.try_reserve(additional));
+    }
+
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
         // Handle zero-sized types
         if std::mem::size_of::<T>() == 0 {
             self.cap = usize::MAX;
-            return;
+            return Ok(());
         }
 
         // Calculate required capacity with overflow checking
         let required_cap = self.cap.checked_add(additional)
-            .unwrap_or(Self::MAX_CAPACITY);
-        
-        // Calculate maximum possible elements based on size
-        let max_elements = std::cmp::min(
-            isize::MAX as usize / std::mem::size_of::<T>().max(1),
-            Self::MAX_CAPACITY
-        );
+            .ok_or(TryReserveError::CapacityOverflow)?;
         
-        // Cap at maximum possible elements
-        let capped_required = std::cmp::min(required_cap, max_elements);
+        // Refuse anything beyond the maximum possible elements
+        let max_elements = Self::max_elements();
+        if required_cap > max_elements {
+            return Err(TryReserveError::CapacityOverflow);
+        }
         
         // If we don't need to grow, return early
-        if capped_required <= self.cap {
-            return;
+        if required_cap <= self.cap {
+            return Ok(());
         }
 
-        // If we're starting from zero, use MIN_NON_ZERO_CAP
-        if self.cap == 0 {
-            let new_cap = std::cmp::max(capped_required, Self::MIN_NON_ZERO_CAP);
-            self.grow_to(new_cap.next_power_of_two());
-            return;
+        // Growth may overshoot for huge requests, so clamp to what can be allocated.
+        // The max guards against policies that return less than required.
+        let new_cap = self.policy.grow(self.cap, required_cap, std::mem::size_of::<T>());
+        let new_cap = std::cmp::min(std::cmp::max(new_cap, required_cap), max_elements);
+        self.try_grow_to(new_cap)
+    }
+
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        if std::mem::size_of::<T>() == 0 {
+            self.cap = usize::MAX;
+            return Ok(());
         }
 
-        // Calculate growth with capped capacity
-        let new_cap = self.calculate_growth(capped_required);
-        self.grow_to(new_cap);
+        let required_cap = self.cap.checked_add(additional)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        if required_cap > Self::max_elements() {
+            return Err(TryReserveError::CapacityOverflow);
+        }
+        if required_cap <= self.cap {
+            return Ok(());
+        }
+
+        // No growth policy: allocate exactly what was asked for
+        self.try_grow_to(required_cap)
     }
 
-    fn grow_to(&mut self, new_cap: usize) {
+    fn try_grow_to(&mut self, new_cap: usize) -> Result<(), TryReserveError> {
         debug_assert!(new_cap > self.cap);
         debug_assert!(new_cap <= Self::MAX_CAPACITY, "Attempted to grow beyond MAX_CAPACITY");
         
         // Handle zero-sized types
         if std::mem::size_of::<T>() == 0 {
             self.cap = new_cap;
-            return;
+            return Ok(());
         }
 
-        // Check if allocation size is reasonable
-        let layout = Layout::array::<T>(new_cap);
-        if layout.is_err() || layout.as_ref().map_or(true, |l| l.size() > isize::MAX as usize) {
-            // If allocation would be too large, cap at current reasonable maximum
-            let max_elements = std::cmp::min(
-                isize::MAX as usize / std::mem::size_of::<T>(),
-                Self::MAX_CAPACITY
-            );
-            if self.cap >= max_elements {
-                return;
-            }
-            self.grow_to(max_elements);
-            return;
-        }
-        
-        let new_layout = layout.unwrap();
+        let new_layout = Layout::array::<T>(new_cap)
+            .map_err(|_| TryReserveError::CapacityOverflow)?;
         
-        unsafe {
-            let new_ptr = if self.cap == 0 {
-                // Fast path: no need to copy when growing from empty
-                alloc::alloc(new_layout) as *mut T
-            } else {
-                // Attempt to use realloc for potential performance gain
-                let old_layout = Layout::array::<T>(self.cap)
-                    .expect("old layout overflow");
-                let new_ptr = alloc::realloc(
-                    self.ptr as *mut u8,
-                    old_layout,
-                    new_layout.size()
-                ) as *mut T;
-                
-                if !new_ptr.is_null() {
-                    // Realloc succeeded
-                    new_ptr
-                } else {
-                    // Fallback to alloc + copy + dealloc
-                    let new_ptr = alloc::alloc(new_layout) as *mut T;
-                    if !new_ptr.is_null() {
-                        ptr::copy_nonoverlapping(self.ptr, new_ptr, self.cap);
-                        alloc::dealloc(self.ptr as *mut u8, old_layout);
-                    }
-                    new_ptr
-                }
-            };
-
-            if new_ptr.is_null() {
-                alloc::handle_alloc_error(new_layout);
+        let result = if self.cap == 0 {
+            // Fast path: no need to copy when growing from empty
+            self.alloc.allocate(new_layout)
+        } else {
+            // Let the allocator grow in place when it can
+            let old_layout = Layout::array::<T>(self.cap)
+                .expect("old layout overflow");
+            unsafe {
+                self.alloc.grow(self.non_null_u8(), old_layout, new_layout)
             }
-            
-            self.ptr = new_ptr;
-            self.cap = new_cap;
-        }
+        };
+
+        // On failure the old buffer is untouched and still owned by us
+        let new_ptr = result
+            .map_err(|_| TryReserveError::AllocError { layout: new_layout })?;
+        self.ptr = new_ptr.as_ptr() as *mut T;
+        self.cap = new_cap;
+        Ok(())
     }
 
-    fn should_shrink(&self, len: usize) -> bool {
-        let elem_size = std::mem::size_of::<T>();
-        
-        // Don't shrink zero-sized types
-        if elem_size == 0 {
-            return false;
-        }
-        
-        // Calculate waste
-        let waste = self.cap - len;
-        let waste_bytes = waste * elem_size;
-        
-        // Calculate usage ratio
-        let usage_ratio = (len as f64) / (self.cap as f64);
-        let waste_ratio = 1.0 - usage_ratio;
-        
-        // Shrink if either:
-        // 1. We're wasting significant memory (>25% AND >1MB)
-        // 2. OR we're using less than 25% of capacity (regardless of size)
-        (waste_bytes > Self::MAX_EXCESS_CAPACITY && waste_ratio > Self::SHRINK_THRESHOLD) ||
-        (usage_ratio < Self::SHRINK_THRESHOLD)
+    fn non_null_u8(&self) -> NonNull<u8> {
+        debug_assert!(!self.ptr.is_null(), "non_null_u8: null pointer");
+        unsafe { NonNull::new_unchecked(self.ptr as *mut u8) }
     }
 
     pub fn shrink_to_fit(&mut self, len: usize) {
@@ -244,36 +230,28 @@ impl<T> RawVec<T> {
             return;
         }
 
-        // Don't shrink if length is close to capacity
-        if len >= self.cap - self.cap / 4 {
+        // Nothing allocated, nothing to shrink
+        if self.cap == 0 {
             return;
         }
 
-        // Only shrink if we should according to our criteria
-        if self.should_shrink(len) {
-            // Calculate new capacity: round up to next power of 2 above len
-            let mut new_cap = len.next_power_of_two();
-            
-            // But don't go below MIN_NON_ZERO_CAP
-            new_cap = std::cmp::max(new_cap, Self::MIN_NON_ZERO_CAP);
-            
-            // Only proceed if we're actually reducing capacity
-            if new_cap < self.cap {
-                unsafe {
-                    let new_layout = Layout::array::<T>(new_cap)
-                        .expect("layout overflow");
-                    let new_ptr = alloc::alloc(new_layout) as *mut T;
-                    
-                    if !new_ptr.is_null() {
-                        ptr::copy_nonoverlapping(self.ptr, new_ptr, len);
-                        let old_layout = Layout::array::<T>(self.cap)
-                            .expect("old layout overflow");
-                        alloc::dealloc(self.ptr as *mut u8, old_layout);
-                        self.ptr = new_ptr;
-                        self.cap = new_cap;
-                    }
-                }
-            }
+        // Ask the policy whether (and how far) to shrink
+        let new_cap = match self.policy.shrink(self.cap, len, std::mem::size_of::<T>()) {
+            Some(new_cap) if new_cap >= std::cmp::max(len, 1) && new_cap < self.cap => new_cap,
+            _ => return,
+        };
+
+        let new_layout = Layout::array::<T>(new_cap)
+            .expect("layout overflow");
+        let old_layout = Layout::array::<T>(self.cap)
+            .expect("old layout overflow");
+
+        // Shrinking is best-effort: keep the old buffer on failure
+        if let Ok(new_ptr) = unsafe {
+            self.alloc.shrink(self.non_null_u8(), old_layout, new_layout)
+        } {
+            self.ptr = new_ptr.as_ptr() as *mut T;
+            self.cap = new_cap;
         }
     }
 
@@ -333,7 +311,7 @@ impl<T> RawVec<T> {
         debug_assert!(index <= self.cap, 
             "shift_right: index out of bounds");
         debug_assert!(index.checked_add(count)
-            .map_or(false, |end| end <= self.cap),
+            .is_some_and(|end| end <= self.cap),
             "shift_right: count extends past capacity");
         debug_assert!(places.checked_add(index).is_some(), 
             "shift_right: places overflow");
@@ -367,7 +345,7 @@ impl<T> RawVec<T> {
             "shift_left: places larger than index");
         debug_assert!(index <= self.cap, 
             "shift_left: index out of bounds");
-        debug_assert!(index.checked_add(count).map_or(false, |end| end <= self.cap),
+        debug_assert!(index.checked_add(count).is_some_and(|end| end <= self.cap),
             "shift_left: count extends past capacity");
         
         // Use checked arithmetic for new position
@@ -386,7 +364,17 @@ impl<T> RawVec<T> {
 
 }
 
-impl<T> Drop for RawVec<T> {
+// Turns a fallible reservation into the infallible behaviour: panic on
+// capacity overflow, abort through handle_alloc_error on allocator failure
+pub(crate) fn handle_reserve<R>(result: Result<R, TryReserveError>) -> R {
+    match result {
+        Ok(value) => value,
+        Err(TryReserveError::CapacityOverflow) => panic!("capacity overflow"),
+        Err(TryReserveError::AllocError { layout }) => alloc::handle_alloc_error(layout),
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Drop for RawVec<T, A, G> {
     fn drop(&mut self) {
         // Don't deallocate if:
         // 1. Capacity is 0 (never allocated)
@@ -395,17 +383,24 @@ impl<T> Drop for RawVec<T> {
             unsafe {
                 let layout: Layout = Layout::array::<T>(self.cap)
                     .expect("Failed to create layout for deallocation");
-                alloc::dealloc(self.ptr as *mut u8, layout);
+                self.alloc.deallocate(self.non_null_u8(), layout);
             }
         }
     }
 } 
 
-impl<T: Clone> Clone for RawVec<T> {
-    fn clone(&self) -> Self {
-        let mut new_vec: RawVec<T> = Self::with_capacity(self.cap);
-        for i in 0..self.cap {
-            new_vec.write_at(i, self.read_at(i).clone());
+impl<T: Clone, A: Allocator + Clone, G: GrowthPolicy + Clone> RawVec<T, A, G> {
+    // Clones the first `len` elements into a new buffer with the same
+    // capacity, policy and allocator. Slots past `len` may be uninitialized,
+    // so the buffer itself can't implement Clone.
+    pub fn clone_prefix(&self, len: usize) -> Self {
+        let mut new_vec = handle_reserve(Self::try_with_capacity_policy_in(
+            self.cap,
+            self.policy.clone(),
+            self.alloc.clone(),
+        ));
+        for i in 0..len {
+            new_vec.write_at(i, self.get_ref(i).clone());
         }
         new_vec
     }
@@ -446,6 +441,25 @@ mod tests {
         assert!(raw.capacity() > old_cap);
     }
 
+    #[test]
+    fn test_raw_vec_try_reserve() {
+        let mut raw: RawVec<i32> = RawVec::new();
+        raw.try_reserve(5).unwrap();
+        assert!(raw.capacity() >= 5);
+        assert_eq!(raw.try_reserve(usize::MAX), Err(TryReserveError::CapacityOverflow));
+        let too_many = RawVec::<i32>::MAX_CAPACITY;
+        assert_eq!(raw.try_reserve(too_many), Err(TryReserveError::CapacityOverflow));
+    }
+
+    #[test]
+    fn test_raw_vec_try_reserve_exact() {
+        let mut raw: RawVec<i32> = RawVec::new();
+        raw.try_reserve_exact(5).unwrap();
+        assert_eq!(raw.capacity(), 5);
+        raw.try_reserve_exact(0).unwrap();
+        assert_eq!(raw.capacity(), 5);
+    }
+
     #[test]
     fn test_raw_vec_read_write() {
         let mut raw: RawVec<i32> = RawVec::with_capacity(2);
@@ -498,7 +512,7 @@ mod tests {
         let mut raw: RawVec<i32> = RawVec::with_capacity(2);
         raw.write_at(0, 1);
         raw.write_at(1, 2);
-        let cloned = raw.clone();
+        let cloned = raw.clone_prefix(2);
         assert_eq!(cloned.read_at(0), 1);
         assert_eq!(cloned.read_at(1), 2);
         assert_eq!(cloned.capacity(), raw.capacity());
@@ -511,7 +525,7 @@ mod tests {
         // Test initial allocation is power of 2
         vec.reserve(1);
         assert!(vec.capacity().is_power_of_two());
-        assert!(vec.capacity() >= RawVec::<i32>::MIN_NON_ZERO_CAP);
+        assert!(vec.capacity() >= TypeSizeAware::MIN_NON_ZERO_CAP);
 
         // Test growth maintains minimum capacity
         let mut last_cap = vec.capacity();
@@ -555,14 +569,21 @@ mod tests {
 
     #[test]
     fn test_type_size_aware_growth() {
+        // reserve() is relative to capacity, so reserve(1) asks for exactly
+        // one slot past the current buffer and exposes the growth step.
+        // reserve(cap + 1) asks for 2 * cap + 1 slots, which is more than any
+        // step; the result is then at least double whatever the type size.
+
         // Test small type (i32 = 4 bytes)
         let mut small_vec = RawVec::<i32>::new();
         small_vec.reserve(10);
         let small_cap1 = small_vec.capacity();
-        small_vec.reserve(small_cap1 + 1);
+        small_vec.reserve(1);
         let small_cap2 = small_vec.capacity();
         // For small types, should grow by ~100%
         assert!(small_cap2 >= small_cap1 * 2);
+        small_vec.reserve(small_cap2 + 1);
+        assert!(small_vec.capacity() >= small_cap2 * 2);
 
         // Test medium type (512 bytes)
         #[derive(Clone)]
@@ -571,11 +592,13 @@ mod tests {
         let mut medium_vec = RawVec::<MediumType>::new();
         medium_vec.reserve(10);
         let med_cap1 = medium_vec.capacity();
-        medium_vec.reserve(med_cap1 + 1);
+        medium_vec.reserve(1);
         let med_cap2 = medium_vec.capacity();
         // For medium types, should grow by ~50%
         assert!(med_cap2 >= med_cap1 + med_cap1 / 2);
         assert!(med_cap2 < med_cap1 * 2);
+        medium_vec.reserve(med_cap2 + 1);
+        assert!(medium_vec.capacity() >= med_cap2 * 2);
 
         // Test large type (2KB)
         #[derive(Clone)]
@@ -584,11 +607,13 @@ mod tests {
         let mut large_vec = RawVec::<LargeType>::new();
         large_vec.reserve(10);
         let large_cap1 = large_vec.capacity();
-        large_vec.reserve(large_cap1 + 1);
+        large_vec.reserve(1);
         let large_cap2 = large_vec.capacity();
         // For large types, should grow by ~25%
         assert!(large_cap2 >= large_cap1 + large_cap1 / 4);
         assert!(large_cap2 < large_cap1 + large_cap1 / 2);
+        large_vec.reserve(large_cap2 + 1);
+        assert!(large_vec.capacity() >= large_cap2 * 2);
     }
 
     #[test]
@@ -0,0 +1,578 @@
+use crate::raw_vec::RawVec;
+use crate::Vec;
+use std::cell::{Cell, UnsafeCell};
+use std::iter::{Flatten, FusedIterator};
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+use std::{fmt, mem, ptr};
+
+// Segment k holds FIRST_SEGMENT << k elements, so segment k starts at
+// FIRST_SEGMENT * (2^k - 1) and an index finds its segment with one log2
+const FIRST_SEGMENT: usize = 8;
+
+/// A growable vector that never moves its elements. Instead of reallocating
+/// it appends segments of doubling size, so references and raw pointers to
+/// elements stay valid for as long as the element is in the vector.
+///
+/// Because growth never invalidates anything, `push` only needs `&self`.
+/// That interior mutability makes the type `!Sync`; methods that remove or
+/// hand out `&mut T` still take `&mut self`.
+pub struct SegmentedVec<T> {
+    // Only the segment list lives in the cell; elements sit in the segments'
+    // own allocations, so borrowing the list never aliases an element
+    segments: UnsafeCell<Vec<RawVec<T>>>,
+    len: Cell<usize>,
+}
+
+/// `SegmentedVec` under the name that says what it guarantees.
+pub type StableVec<T> = SegmentedVec<T>;
+
+impl<T> SegmentedVec<T> {
+    pub fn new() -> Self {
+        SegmentedVec {
+            segments: UnsafeCell::new(Vec::new()),
+            len: Cell::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Total capacity of the allocated segments.
+    pub fn capacity(&self) -> usize {
+        self.segment_list().iter().map(RawVec::capacity).sum()
+    }
+
+    // Short-lived view of the segment list. Never held across a push.
+    fn segment_list(&self) -> &Vec<RawVec<T>> {
+        unsafe { &*self.segments.get() }
+    }
+
+    // Segment index and offset of the element at `index`. ZSTs never
+    // allocate, so the first segment already has room for all of them.
+    fn locate(index: usize) -> (usize, usize) {
+        if mem::size_of::<T>() == 0 {
+            return (0, index);
+        }
+        let segment = (index / FIRST_SEGMENT + 1).ilog2() as usize;
+        (segment, index - Self::segment_start(segment))
+    }
+
+    fn segment_start(segment: usize) -> usize {
+        if mem::size_of::<T>() == 0 {
+            0
+        } else {
+            FIRST_SEGMENT * ((1 << segment) - 1)
+        }
+    }
+
+    // Number of segments holding at least one element
+    fn segments_in_use(&self) -> usize {
+        match self.len() {
+            0 => 0,
+            len => Self::locate(len - 1).0 + 1,
+        }
+    }
+
+    fn element_ptr(&self, index: usize) -> *mut T {
+        let (segment, offset) = Self::locate(index);
+        unsafe { self.segment_list()[segment].ptr().add(offset) }
+    }
+
+    /// Appends `item` and returns a reference to it. Takes `&self`: existing
+    /// elements never move, so outstanding references stay valid.
+    pub fn push(&self, item: T) -> &T {
+        let len = self.len();
+        let (segment, offset) = Self::locate(len);
+
+        // No reference into the list is alive here: every other `&self`
+        // method copies out what it needs before returning
+        let segments = unsafe { &mut *self.segments.get() };
+        if segment == segments.len() {
+            segments.push(RawVec::with_capacity(FIRST_SEGMENT << segment));
+        }
+        segments[segment].write_at(offset, item);
+        self.len.set(len + 1);
+        unsafe { &*self.element_ptr(len) }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let len = self.len();
+        if len == 0 {
+            return None;
+        }
+        self.len.set(len - 1);
+        Some(unsafe { ptr::read(self.element_ptr(len - 1)) })
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len() {
+            Some(unsafe { &*self.element_ptr(index) })
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len() {
+            Some(unsafe { &mut *self.element_ptr(index) })
+        } else {
+            None
+        }
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        // Shorten first so a panicking destructor can't cause a double drop
+        while self.len() > len {
+            drop(self.pop());
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Frees the segments past the last element. Never moves an element.
+    pub fn shrink_to_fit(&mut self) {
+        let in_use = self.segments_in_use();
+        self.segments.get_mut().truncate(in_use);
+    }
+
+    /// The filled part of each segment, in order.
+    pub fn segments(&self) -> Segments<'_, T> {
+        Segments { vec: self, front: 0, back: self.segments_in_use(), len: self.len() }
+    }
+
+    pub fn segments_mut(&mut self) -> SegmentsMut<'_, T> {
+        let back = self.segments_in_use();
+        SegmentsMut { vec: self, front: 0, back, _marker: PhantomData }
+    }
+
+    // Filled part of `segment` when the vector holds `len` elements
+    fn segment_slice(&self, segment: usize, len: usize) -> *mut [T] {
+        let raw = &self.segment_list()[segment];
+        let start = Self::segment_start(segment);
+        let filled = (len - start).min(raw.capacity());
+        ptr::slice_from_raw_parts_mut(raw.ptr(), filled)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { inner: self.segments().flatten(), remaining: self.len() }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let remaining = self.len();
+        IterMut { inner: self.segments_mut().flatten(), remaining }
+    }
+}
+
+impl<T> Drop for SegmentedVec<T> {
+    fn drop(&mut self) {
+        // Drop the elements segment by segment; the RawVecs free the memory
+        let (len, in_use) = (self.len(), self.segments_in_use());
+        self.len.set(0);
+        for segment in 0..in_use {
+            unsafe { ptr::drop_in_place(self.segment_slice(segment, len)) };
+        }
+    }
+}
+
+impl<T> Index<usize> for SegmentedVec<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(item) => item,
+            None => panic!("index (is {}) should be < len (is {})", index, self.len()),
+        }
+    }
+}
+
+impl<T> IndexMut<usize> for SegmentedVec<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len();
+        match self.get_mut(index) {
+            Some(item) => item,
+            None => panic!("index (is {}) should be < len (is {})", index, len),
+        }
+    }
+}
+
+impl<T: Clone> Clone for SegmentedVec<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SegmentedVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for SegmentedVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for SegmentedVec<T> {}
+
+impl<T> Default for SegmentedVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Extend<T> for SegmentedVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for SegmentedVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = SegmentedVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T> IntoIterator for SegmentedVec<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        // The iterator owns the elements from here on; the vector only keeps
+        // the segments to free them
+        let end = self.len();
+        self.len.set(0);
+        IntoIter { vec: self, start: 0, end }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SegmentedVec<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut SegmentedVec<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Iterator over a `SegmentedVec`'s segments as slices. Covers the elements
+/// present when it was created, even if more are pushed meanwhile.
+pub struct Segments<'a, T> {
+    vec: &'a SegmentedVec<T>,
+    front: usize,
+    back: usize,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Segments<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<&'a [T]> {
+        if self.front == self.back {
+            return None;
+        }
+        let slice = self.vec.segment_slice(self.front, self.len);
+        self.front += 1;
+        Some(unsafe { &*slice })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Segments<'a, T> {
+    fn next_back(&mut self) -> Option<&'a [T]> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(unsafe { &*self.vec.segment_slice(self.back, self.len) })
+    }
+}
+
+impl<T> ExactSizeIterator for Segments<'_, T> {}
+impl<T> FusedIterator for Segments<'_, T> {}
+
+/// Mutable counterpart of `Segments`.
+pub struct SegmentsMut<'a, T> {
+    vec: *const SegmentedVec<T>,
+    front: usize,
+    back: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> Iterator for SegmentsMut<'a, T> {
+    type Item = &'a mut [T];
+
+    fn next(&mut self) -> Option<&'a mut [T]> {
+        if self.front == self.back {
+            return None;
+        }
+        let vec = unsafe { &*self.vec };
+        let slice = vec.segment_slice(self.front, vec.len());
+        self.front += 1;
+        Some(unsafe { &mut *slice })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for SegmentsMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut [T]> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        let vec = unsafe { &*self.vec };
+        Some(unsafe { &mut *vec.segment_slice(self.back, vec.len()) })
+    }
+}
+
+impl<T> ExactSizeIterator for SegmentsMut<'_, T> {}
+impl<T> FusedIterator for SegmentsMut<'_, T> {}
+
+/// Element iterator for `SegmentedVec`; walks each segment as a slice.
+pub struct Iter<'a, T> {
+    inner: Flatten<Segments<'a, T>>,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let item = self.inner.next()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        let item = self.inner.next_back()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// Mutable element iterator for `SegmentedVec`.
+pub struct IterMut<'a, T> {
+    inner: Flatten<SegmentsMut<'a, T>>,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        let item = self.inner.next()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        let item = self.inner.next_back()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+/// Consuming iterator for `SegmentedVec`.
+pub struct IntoIter<T> {
+    vec: SegmentedVec<T>, // Length already zeroed; only frees the segments
+    start: usize,
+    end: usize,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.start += 1;
+        Some(unsafe { ptr::read(self.vec.element_ptr(self.start - 1)) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(unsafe { ptr::read(self.vec.element_ptr(self.end)) })
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T> Drop for IntoIter<T> {
+    fn drop(&mut self) {
+        for item in self.by_ref() {
+            drop(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate() {
+        let cases = [(0, (0, 0)), (7, (0, 7)), (8, (1, 0)), (23, (1, 15)), (24, (2, 0)), (56, (3, 0))];
+        for (index, expected) in cases {
+            assert_eq!(SegmentedVec::<u32>::locate(index), expected);
+        }
+        assert_eq!(SegmentedVec::<()>::locate(1000), (0, 1000));
+    }
+
+    #[test]
+    fn test_push_through_shared_reference() {
+        let vec = StableVec::new();
+        let first = vec.push(String::from("first"));
+        let first_ptr = first as *const String;
+
+        // Holding `first` across many pushes is fine: nothing moves
+        for i in 0..1000 {
+            vec.push(i.to_string());
+        }
+        assert_eq!(first, "first");
+        assert_eq!(&vec[0] as *const String, first_ptr);
+        assert_eq!(vec[1000], "999");
+        assert_eq!(vec.len(), 1001);
+    }
+
+    #[test]
+    fn test_segments() {
+        let vec: SegmentedVec<u32> = (0..30).collect();
+        let lens: std::vec::Vec<usize> = vec.segments().map(<[u32]>::len).collect();
+        assert_eq!(lens, [8, 16, 6]);
+        assert_eq!(vec.capacity(), 8 + 16 + 32);
+        assert_eq!(vec.segments().next_back(), Some(&[24, 25, 26, 27, 28, 29][..]));
+
+        // A snapshot: pushing during iteration isn't observed
+        let mut count = 0;
+        for segment in vec.segments() {
+            count += segment.len();
+            vec.push(0);
+        }
+        assert_eq!(count, 30);
+        assert_eq!(vec.len(), 33);
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut vec: SegmentedVec<i32> = (0..20).collect();
+        assert!(vec.iter().copied().eq(0..20));
+        assert!(vec.iter().rev().copied().eq((0..20).rev()));
+        assert_eq!(vec.iter().len(), 20);
+
+        for x in &mut vec {
+            *x *= 2;
+        }
+        for segment in vec.segments_mut() {
+            segment[0] = -1;
+        }
+        assert_eq!(vec[0], -1);
+        assert_eq!(vec[8], -1);
+        assert_eq!(vec[9], 18);
+
+        let mut iter = vec.into_iter();
+        assert_eq!(iter.next_back(), Some(38));
+        assert_eq!(iter.next(), Some(-1));
+        assert_eq!(iter.len(), 18);
+    }
+
+    #[test]
+    fn test_pop_and_shrink_keep_addresses() {
+        let mut vec: SegmentedVec<u64> = (0..40).collect();
+        let ptr = &vec[5] as *const u64;
+        while vec.len() > 10 {
+            vec.pop();
+        }
+        vec.shrink_to_fit();
+        assert_eq!(vec.capacity(), 8 + 16);
+        assert_eq!(&vec[5] as *const u64, ptr);
+        assert_eq!(vec.pop(), Some(9));
+    }
+
+    #[test]
+    fn test_drops() {
+        use std::rc::Rc;
+
+        let marker = Rc::new(());
+        let vec: SegmentedVec<Rc<()>> = (0..50).map(|_| marker.clone()).collect();
+        let mut iter = vec.into_iter();
+        iter.next();
+        iter.next_back();
+        drop(iter);
+        assert_eq!(Rc::strong_count(&marker), 1);
+
+        let mut vec: SegmentedVec<Rc<()>> = (0..50).map(|_| marker.clone()).collect();
+        vec.truncate(20);
+        assert_eq!(Rc::strong_count(&marker), 21);
+        drop(vec);
+        assert_eq!(Rc::strong_count(&marker), 1);
+    }
+
+    #[test]
+    fn test_zero_sized() {
+        let vec = SegmentedVec::new();
+        for _ in 0..100 {
+            vec.push(());
+        }
+        assert_eq!(vec.segments().len(), 1);
+        assert_eq!(vec.iter().count(), 100);
+    }
+}
@@ -0,0 +1,189 @@
+// Serde support, behind the `serde` feature. All three vectors serialize as
+// plain sequences, so they are interchangeable on the wire with each other
+// and with `std::vec::Vec`.
+
+use crate::allocator::Allocator;
+use crate::growth::GrowthPolicy;
+use crate::{ArrayVec, SmallVec, Vec};
+use serde::de::{Deserialize, Deserializer, Error, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+// A sequence's size hint comes from the input and can't be trusted, so
+// deserialization pre-reserves at most this many bytes up front and grows
+// normally past it
+const MAX_PREALLOC_BYTES: usize = 1024 * 1024;
+
+fn cautious_capacity<T>(hint: Option<usize>) -> usize {
+    let limit = MAX_PREALLOC_BYTES / std::mem::size_of::<T>().max(1);
+    hint.unwrap_or(0).min(limit)
+}
+
+impl<T: Serialize, A: Allocator, G: GrowthPolicy> Serialize for Vec<T, A, G> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T, A, G> Deserialize<'de> for Vec<T, A, G>
+where
+    T: Deserialize<'de>,
+    A: Allocator + Default,
+    G: GrowthPolicy + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct VecVisitor<T, A, G>(PhantomData<(T, A, G)>);
+
+        impl<'de, T, A, G> Visitor<'de> for VecVisitor<T, A, G>
+        where
+            T: Deserialize<'de>,
+            A: Allocator + Default,
+            G: GrowthPolicy + Default,
+        {
+            type Value = Vec<T, A, G>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+                let mut vec = Vec::with_policy_in(G::default(), A::default());
+                vec.reserve_spare(cautious_capacity::<T>(seq.size_hint()));
+                while let Some(item) = seq.next_element()? {
+                    vec.push(item);
+                }
+                Ok(vec)
+            }
+        }
+
+        deserializer.deserialize_seq(VecVisitor(PhantomData))
+    }
+}
+
+impl<T: Serialize, const N: usize> Serialize for SmallVec<T, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T: Deserialize<'de>, const N: usize> Deserialize<'de> for SmallVec<T, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SmallVecVisitor<T, const N: usize>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>, const N: usize> Visitor<'de> for SmallVecVisitor<T, N> {
+            type Value = SmallVec<T, N>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+                // Hints that fit inline don't allocate
+                let mut vec = SmallVec::with_capacity(cautious_capacity::<T>(seq.size_hint()));
+                while let Some(item) = seq.next_element()? {
+                    vec.push(item);
+                }
+                Ok(vec)
+            }
+        }
+
+        deserializer.deserialize_seq(SmallVecVisitor(PhantomData))
+    }
+}
+
+impl<T: Serialize, const N: usize> Serialize for ArrayVec<T, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+// Nothing to pre-reserve; a sequence longer than `N` is an error rather than
+// being truncated
+impl<'de, T: Deserialize<'de>, const N: usize> Deserialize<'de> for ArrayVec<T, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ArrayVecVisitor<T, const N: usize>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>, const N: usize> Visitor<'de> for ArrayVecVisitor<T, N> {
+            type Value = ArrayVec<T, N>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a sequence of at most {} elements", N)
+            }
+
+            fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+                let mut vec = ArrayVec::new();
+                while let Some(item) = seq.next_element()? {
+                    if vec.push(item).is_err() {
+                        return Err(S::Error::invalid_length(N + 1, &self));
+                    }
+                }
+                Ok(vec)
+            }
+        }
+
+        deserializer.deserialize_seq(ArrayVecVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::value::{self, SeqDeserializer};
+    use serde_test::{assert_de_tokens, assert_ser_tokens, assert_tokens, Token};
+
+    #[test]
+    fn test_vec_tokens() {
+        let vec: Vec<u32> = crate::vec![1, 2, 3];
+        assert_tokens(
+            &vec,
+            &[Token::Seq { len: Some(3) }, Token::U32(1), Token::U32(2), Token::U32(3), Token::SeqEnd],
+        );
+
+        let empty: Vec<u32> = Vec::new();
+        assert_tokens(&empty, &[Token::Seq { len: Some(0) }, Token::SeqEnd]);
+    }
+
+    #[test]
+    fn test_hostile_size_hint_is_capped() {
+        // Claims far more elements than it delivers
+        let vec: Vec<u64> = Vec::new();
+        assert_de_tokens(&vec, &[Token::Seq { len: Some(usize::MAX) }, Token::SeqEnd]);
+
+        assert_eq!(cautious_capacity::<u64>(Some(usize::MAX)), MAX_PREALLOC_BYTES / 8);
+        assert_eq!(cautious_capacity::<u64>(Some(10)), 10);
+        assert_eq!(cautious_capacity::<()>(Some(usize::MAX)), MAX_PREALLOC_BYTES);
+        assert_eq!(cautious_capacity::<u8>(None), 0);
+    }
+
+    fn from_seq<'de, V: Deserialize<'de>>(items: &[u8]) -> Result<V, value::Error> {
+        V::deserialize(SeqDeserializer::new(items.iter().copied()))
+    }
+
+    #[test]
+    fn test_small_vec() {
+        let mut vec: SmallVec<u8, 2> = SmallVec::new();
+        vec.extend([7, 8, 9]);
+        assert_tokens(
+            &vec,
+            &[Token::Seq { len: Some(3) }, Token::U8(7), Token::U8(8), Token::U8(9), Token::SeqEnd],
+        );
+
+        let inline: SmallVec<u8, 2> = from_seq(&[1, 2]).unwrap();
+        assert!(!inline.spilled());
+        assert_eq!(inline, [1, 2]);
+        let spilled: SmallVec<u8, 2> = from_seq(&[1, 2, 3]).unwrap();
+        assert_eq!(spilled, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_array_vec_overflow() {
+        let vec: ArrayVec<u8, 2> = [4, 5].into_iter().collect();
+        assert_ser_tokens(&vec, &[Token::Seq { len: Some(2) }, Token::U8(4), Token::U8(5), Token::SeqEnd]);
+
+        let full: ArrayVec<u8, 2> = from_seq(&[4, 5]).unwrap();
+        assert_eq!(full, vec);
+        let err = from_seq::<ArrayVec<u8, 2>>(&[1, 2, 3]).unwrap_err();
+        assert_eq!(err.to_string(), "invalid length 3, expected a sequence of at most 2 elements");
+    }
+}
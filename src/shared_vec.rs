@@ -0,0 +1,430 @@
+use crate::allocator::{Allocator, Global};
+use crate::growth::GrowthPolicy;
+use crate::Vec;
+use std::alloc::{self, Layout};
+use std::cell::Cell;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{self, AtomicUsize, Ordering};
+use std::{fmt, slice};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Reference counter stored in a `SharedVec`'s header: `AtomicUsize` for
+/// `ArcVec`, `Cell<usize>` for `RcVec`. Sealed.
+pub trait RefCount: private::Sealed {
+    #[doc(hidden)]
+    fn one() -> Self;
+    #[doc(hidden)]
+    fn increment(&self);
+    // Returns true when the last reference went away
+    #[doc(hidden)]
+    fn decrement(&self) -> bool;
+    #[doc(hidden)]
+    fn is_unique(&self) -> bool;
+    #[doc(hidden)]
+    fn get(&self) -> usize;
+}
+
+// Like std's Arc: a count this large means clones are being leaked, and
+// wrapping around would free the buffer while it's still in use
+const MAX_REFCOUNT: usize = isize::MAX as usize;
+
+impl private::Sealed for AtomicUsize {}
+
+impl RefCount for AtomicUsize {
+    fn one() -> Self {
+        AtomicUsize::new(1)
+    }
+
+    fn increment(&self) {
+        // A new reference can only come from an existing one, so no
+        // ordering is needed
+        if self.fetch_add(1, Ordering::Relaxed) > MAX_REFCOUNT {
+            std::process::abort();
+        }
+    }
+
+    fn decrement(&self) -> bool {
+        // Release publishes this owner's writes; the Acquire fence makes them
+        // visible to whoever frees the buffer
+        if self.fetch_sub(1, Ordering::Release) != 1 {
+            return false;
+        }
+        atomic::fence(Ordering::Acquire);
+        true
+    }
+
+    fn is_unique(&self) -> bool {
+        self.load(Ordering::Acquire) == 1
+    }
+
+    fn get(&self) -> usize {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+impl private::Sealed for Cell<usize> {}
+
+impl RefCount for Cell<usize> {
+    fn one() -> Self {
+        Cell::new(1)
+    }
+
+    fn increment(&self) {
+        if self.get() >= MAX_REFCOUNT {
+            std::process::abort();
+        }
+        self.set(self.get() + 1);
+    }
+
+    fn decrement(&self) -> bool {
+        self.set(self.get() - 1);
+        self.get() == 0
+    }
+
+    fn is_unique(&self) -> bool {
+        self.get() == 1
+    }
+
+    fn get(&self) -> usize {
+        Cell::get(self)
+    }
+}
+
+// Sits at the start of the allocation, followed by the elements
+#[repr(C)]
+struct Header<C> {
+    count: C,
+    len: usize,
+}
+
+/// An immutable, reference-counted vector. Cloning bumps a counter instead
+/// of copying the elements; `make_mut` copies only when the buffer is shared.
+///
+/// The counter and length live in a header in the same allocation as the
+/// elements, so a `SharedVec` is one pointer and sharing costs no extra
+/// allocation.
+pub struct SharedVec<T, C: RefCount> {
+    ptr: NonNull<Header<C>>,
+    _marker: PhantomData<T>,
+}
+
+/// Thread-safe `SharedVec` with an atomic counter.
+pub type ArcVec<T> = SharedVec<T, AtomicUsize>;
+
+/// Single-threaded `SharedVec` with a plain counter.
+pub type RcVec<T> = SharedVec<T, Cell<usize>>;
+
+// Same rules as Arc<[T]>; RcVec stays !Send and !Sync
+unsafe impl<T: Send + Sync> Send for SharedVec<T, AtomicUsize> {}
+unsafe impl<T: Send + Sync> Sync for SharedVec<T, AtomicUsize> {}
+
+impl<T, C: RefCount> SharedVec<T, C> {
+    pub fn new() -> Self {
+        Self::from_vec(Vec::new())
+    }
+
+    // Layout of a header followed by `len` elements, and the elements' offset
+    fn layout(len: usize) -> (Layout, usize) {
+        let elements = Layout::array::<T>(len).expect("capacity overflow");
+        let (layout, offset) = Layout::new::<Header<C>>()
+            .extend(elements)
+            .expect("capacity overflow");
+        (layout.pad_to_align(), offset)
+    }
+
+    fn header(&self) -> &Header<C> {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    fn elements(&self) -> *mut T {
+        let (_, offset) = Self::layout(0);
+        unsafe { (self.ptr.as_ptr() as *mut u8).add(offset) as *mut T }
+    }
+
+    // Moves the elements out of `vec` into a fresh header allocation
+    fn from_vec<A: Allocator, G: GrowthPolicy>(mut vec: Vec<T, A, G>) -> Self {
+        let len = vec.len();
+        let (layout, _) = Self::layout(len);
+        let ptr = match Global.allocate(layout) {
+            Ok(ptr) => ptr.cast::<Header<C>>(),
+            Err(_) => alloc::handle_alloc_error(layout),
+        };
+        unsafe {
+            ptr.as_ptr().write(Header { count: C::one(), len });
+            let shared = SharedVec { ptr, _marker: PhantomData };
+            ptr::copy_nonoverlapping(vec.as_ptr(), shared.elements(), len);
+            // The elements now belong to `shared`; `vec` only frees its buffer
+            vec.set_len(0);
+            shared
+        }
+    }
+
+    /// Number of `SharedVec`s pointing at this buffer.
+    pub fn strong_count(this: &Self) -> usize {
+        this.header().count.get()
+    }
+
+    /// Whether both point at the same buffer.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.ptr == other.ptr
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.elements(), self.header().len) }
+    }
+
+    /// Mutable access without copying, if no other `SharedVec` shares the
+    /// buffer.
+    pub fn get_mut(this: &mut Self) -> Option<&mut [T]> {
+        if this.header().count.is_unique() {
+            Some(unsafe { slice::from_raw_parts_mut(this.elements(), this.header().len) })
+        } else {
+            None
+        }
+    }
+
+    /// Turns the last reference into a `Vec`, or hands `this` back if the
+    /// buffer is shared. The elements move with one memcpy into a `Vec`
+    /// buffer, since that can't carry the header; nothing is cloned.
+    pub fn try_unwrap(this: Self) -> Result<Vec<T>, Self> {
+        if !this.header().count.is_unique() {
+            return Err(this);
+        }
+
+        let this = ManuallyDrop::new(this);
+        let len = this.header().len;
+        let mut vec = Vec::with_capacity(len);
+        unsafe {
+            ptr::copy_nonoverlapping(this.elements(), vec.as_mut_ptr(), len);
+            vec.set_len(len);
+            this.deallocate();
+        }
+        Ok(vec)
+    }
+
+    // Frees the allocation without dropping the elements
+    unsafe fn deallocate(&self) {
+        let (layout, _) = Self::layout(self.header().len);
+        Global.deallocate(self.ptr.cast(), layout);
+    }
+}
+
+impl<T: Clone, C: RefCount> SharedVec<T, C> {
+    /// Mutable access, first copying the elements into a buffer of its own
+    /// if this one is shared (copy-on-write).
+    pub fn make_mut(this: &mut Self) -> &mut [T] {
+        if !this.header().count.is_unique() {
+            *this = Self::from(this.as_slice());
+        }
+        unsafe { slice::from_raw_parts_mut(this.elements(), this.header().len) }
+    }
+
+    /// `try_unwrap`, falling back to cloning the elements when shared.
+    pub fn unwrap_or_clone(this: Self) -> Vec<T> {
+        Self::try_unwrap(this).unwrap_or_else(|shared| {
+            let mut vec = Vec::with_capacity(shared.len());
+            vec.extend_from_slice(&shared);
+            vec
+        })
+    }
+}
+
+impl<T, C: RefCount> Clone for SharedVec<T, C> {
+    fn clone(&self) -> Self {
+        self.header().count.increment();
+        SharedVec { ptr: self.ptr, _marker: PhantomData }
+    }
+}
+
+impl<T, C: RefCount> Drop for SharedVec<T, C> {
+    fn drop(&mut self) {
+        if !self.header().count.decrement() {
+            return;
+        }
+
+        // Free the allocation even if an element's destructor panics
+        struct Deallocate<'a, T, C: RefCount>(&'a SharedVec<T, C>);
+
+        impl<T, C: RefCount> Drop for Deallocate<'_, T, C> {
+            fn drop(&mut self) {
+                unsafe { self.0.deallocate() };
+            }
+        }
+
+        let _guard = Deallocate(self);
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.elements(), self.header().len));
+        }
+    }
+}
+
+impl<T, C: RefCount> Deref for SharedVec<T, C> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, C: RefCount> AsRef<[T]> for SharedVec<T, C> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy, C: RefCount> From<Vec<T, A, G>> for SharedVec<T, C> {
+    fn from(vec: Vec<T, A, G>) -> Self {
+        Self::from_vec(vec)
+    }
+}
+
+impl<T: Clone, C: RefCount> From<&[T]> for SharedVec<T, C> {
+    fn from(slice: &[T]) -> Self {
+        let mut vec = Vec::with_capacity(slice.len());
+        vec.extend_from_slice(slice);
+        Self::from_vec(vec)
+    }
+}
+
+impl<T, C: RefCount> FromIterator<T> for SharedVec<T, C> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect::<Vec<T>>())
+    }
+}
+
+impl<T, C: RefCount> Default for SharedVec<T, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug, C: RefCount> fmt::Debug for SharedVec<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq, C: RefCount> PartialEq for SharedVec<T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        Self::ptr_eq(self, other) || **self == **other
+    }
+}
+
+impl<T: Eq, C: RefCount> Eq for SharedVec<T, C> {}
+
+impl<T: Hash, C: RefCount> Hash for SharedVec<T, C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+impl<'a, T, C: RefCount> IntoIterator for &'a SharedVec<T, C> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_clone_shares_buffer() {
+        let a: ArcVec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        let b = a.clone();
+        assert!(ArcVec::ptr_eq(&a, &b));
+        assert_eq!(ArcVec::strong_count(&a), 2);
+        assert_eq!(b[1], "b");
+        assert_eq!(a, b);
+
+        drop(b);
+        assert_eq!(ArcVec::strong_count(&a), 1);
+    }
+
+    #[test]
+    fn test_make_mut_copies_only_when_shared() {
+        let mut a: RcVec<i32> = (0..4).collect();
+        let ptr = a.as_ptr();
+        RcVec::make_mut(&mut a)[0] = 10;
+        assert_eq!(a.as_ptr(), ptr);
+
+        let b = a.clone();
+        RcVec::make_mut(&mut a)[1] = 11;
+        assert_ne!(a.as_ptr(), ptr);
+        assert_eq!(*a, [10, 11, 2, 3]);
+        assert_eq!(*b, [10, 1, 2, 3]);
+        assert_eq!(RcVec::strong_count(&b), 1);
+    }
+
+    #[test]
+    fn test_get_mut_and_try_unwrap() {
+        let mut a: RcVec<i32> = (0..3).collect();
+        let b = a.clone();
+        assert!(RcVec::get_mut(&mut a).is_none());
+
+        let a = RcVec::try_unwrap(a).unwrap_err();
+        drop(b);
+        let vec = RcVec::try_unwrap(a).unwrap();
+        assert_eq!(vec, [0, 1, 2]);
+
+        let a: RcVec<i32> = vec.into();
+        let b = a.clone();
+        assert_eq!(RcVec::unwrap_or_clone(a), [0, 1, 2]);
+        assert_eq!(RcVec::unwrap_or_clone(b), [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_drops_elements_once() {
+        let marker = Rc::new(());
+        let a: RcVec<Rc<()>> = (0..5).map(|_| marker.clone()).collect();
+        let b = a.clone();
+        drop(a);
+        assert_eq!(Rc::strong_count(&marker), 6);
+        drop(b);
+        assert_eq!(Rc::strong_count(&marker), 1);
+
+        // try_unwrap moves the elements, it doesn't drop or clone them
+        let a: RcVec<Rc<()>> = (0..5).map(|_| marker.clone()).collect();
+        let vec = RcVec::try_unwrap(a).unwrap();
+        assert_eq!(Rc::strong_count(&marker), 6);
+        drop(vec);
+        assert_eq!(Rc::strong_count(&marker), 1);
+    }
+
+    #[test]
+    fn test_empty_and_zero_sized() {
+        let empty: ArcVec<u64> = ArcVec::new();
+        assert!(empty.is_empty());
+        assert_eq!(empty.clone(), empty);
+
+        let units: ArcVec<()> = std::iter::repeat_n((), 7).collect();
+        assert_eq!(units.len(), 7);
+        assert_eq!(ArcVec::try_unwrap(units).unwrap().len(), 7);
+    }
+
+    #[test]
+    fn test_arc_vec_across_threads() {
+        let shared: ArcVec<u64> = (0..1000).collect();
+        let handles: std::vec::Vec<_> = (0..4)
+            .map(|_| {
+                let shared = shared.clone();
+                std::thread::spawn(move || shared.iter().sum::<u64>())
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 499_500);
+        }
+        assert_eq!(ArcVec::strong_count(&shared), 1);
+    }
+}
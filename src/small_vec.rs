@@ -0,0 +1,854 @@
+use crate::allocator::Global;
+use crate::buffer::Buffer;
+use crate::error::{GetManyMutError, TryReserveError};
+use crate::index::{self, VecIndex};
+use crate::iter::{self, Iter, IterMut};
+use crate::range::slice_range;
+use crate::raw_vec::{handle_reserve, RawVec};
+use crate::Vec;
+use std::hash::{Hash, Hasher};
+use std::mem::{self, ManuallyDrop, MaybeUninit};
+use std::ops::{Deref, DerefMut, Index, IndexMut, RangeBounds};
+use std::slice::SliceIndex;
+use std::{cmp, ptr, slice};
+
+enum Storage<T, const N: usize> {
+    Inline(MaybeUninit<[T; N]>), // Up to N elements without touching the heap
+    Heap(RawVec<T>),             // Spilled: same buffer management as Vec
+}
+
+/// A vector that stores up to `N` elements inline and spills to a heap
+/// `RawVec` once it outgrows them.
+pub struct SmallVec<T, const N: usize> {
+    data: Storage<T, N>,
+    len: usize,
+}
+
+impl<T, const N: usize> SmallVec<T, N> {
+    pub fn new() -> Self {
+        SmallVec {
+            data: Storage::Inline(MaybeUninit::uninit()),
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        handle_reserve(Self::try_with_capacity(capacity))
+    }
+
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+        if capacity <= N {
+            Ok(Self::new())
+        } else {
+            Ok(SmallVec {
+                data: Storage::Heap(RawVec::try_with_capacity_in(capacity, Global)?),
+                len: 0,
+            })
+        }
+    }
+
+    /// Returns `true` once the elements have moved to the heap.
+    pub fn spilled(&self) -> bool {
+        matches!(self.data, Storage::Heap(_))
+    }
+
+    pub fn capacity(&self) -> usize {
+        match &self.data {
+            Storage::Inline(_) => N,
+            Storage::Heap(buf) => buf.capacity(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a pointer to the elements, inline or on the heap. Spilling
+    /// moves them, so the pointer is only stable while no growth happens.
+    pub fn as_ptr(&self) -> *const T {
+        match &self.data {
+            Storage::Inline(array) => array.as_ptr() as *const T,
+            Storage::Heap(buf) => buf.ptr(),
+        }
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        match &mut self.data {
+            Storage::Inline(array) => array.as_mut_ptr() as *mut T,
+            Storage::Heap(buf) => buf.ptr(),
+        }
+    }
+
+    /// Sets the length without touching the elements.
+    ///
+    /// # Safety
+    ///
+    /// `new_len` must be at most `capacity()`, and the elements up to
+    /// `new_len` must be initialized.
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        debug_assert!(new_len <= self.capacity());
+        self.len = new_len;
+    }
+
+    /// Ensures room for `additional` more elements, spilling to the heap
+    /// only when the inline buffer is too small.
+    pub fn reserve(&mut self, additional: usize) {
+        handle_reserve(self.try_reserve(additional));
+    }
+
+    /// Fallible counterpart of `reserve`: reports capacity overflow or
+    /// allocator failure instead of panicking or aborting.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let spare = self.capacity() - self.len;
+        if additional <= spare {
+            return Ok(());
+        }
+
+        if let Storage::Heap(buf) = &mut self.data {
+            buf.try_reserve(additional - spare)
+        } else {
+            let required = self.len.checked_add(additional)
+                .ok_or(TryReserveError::CapacityOverflow)?;
+            self.try_spill(required)
+        }
+    }
+
+    // Moves the inline elements into a heap buffer holding at least `required`.
+    // All N slots are copied, so a tail a Drain parked past `len` comes along.
+    // On failure the elements stay inline.
+    fn try_spill(&mut self, required: usize) -> Result<(), TryReserveError> {
+        debug_assert!(!self.spilled() && required >= N);
+
+        let mut buf = RawVec::new();
+        buf.try_reserve(required)?;
+        unsafe {
+            ptr::copy_nonoverlapping(self.as_ptr(), buf.ptr(), N);
+        }
+        // The inline array is MaybeUninit, so replacing it drops nothing
+        self.data = Storage::Heap(buf);
+        Ok(())
+    }
+
+    pub fn push(&mut self, item: T) {
+        if self.len == self.capacity() {
+            self.reserve(1);
+        }
+        unsafe {
+            ptr::write(self.as_mut_ptr().add(self.len), item);
+        }
+        self.len += 1;
+    }
+
+    pub fn try_push(&mut self, item: T) -> Result<(), TryReserveError> {
+        if self.len == self.capacity() {
+            self.try_reserve(1)?;
+        }
+        unsafe {
+            ptr::write(self.as_mut_ptr().add(self.len), item);
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Fallible counterpart of `extend`. Stops at the first failed
+    /// allocation; elements pushed before the failure are kept.
+    pub fn try_extend<I: IntoIterator<Item = T>>(&mut self, iter: I) -> Result<(), TryReserveError> {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        self.try_reserve(lower)?;
+
+        for item in iter {
+            self.try_push(item)?;
+        }
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            Some(unsafe { ptr::read(self.as_ptr().add(self.len)) })
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, item: T) {
+        if index > self.len {
+            panic!(
+                "insertion index (is {}) should be <= len (is {})",
+                index, self.len
+            );
+        }
+
+        if self.len == self.capacity() {
+            self.reserve(1);
+        }
+
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            ptr::copy(p, p.add(1), self.len - index);
+            ptr::write(p, item);
+        }
+        self.len += 1;
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        if index >= self.len {
+            panic!(
+                "removal index (is {}) should be < len (is {})",
+                index, self.len
+            );
+        }
+
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            let item = ptr::read(p);
+            ptr::copy(p.add(1), p, self.len - index - 1);
+            self.len -= 1;
+            item
+        }
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+
+        // Shorten first so a panicking destructor can't cause a double drop
+        let tail = ptr::slice_from_raw_parts_mut(
+            unsafe { self.as_mut_ptr().add(len) },
+            self.len - len,
+        );
+        self.len = len;
+        unsafe {
+            ptr::drop_in_place(tail);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Moves the elements back inline when they fit, otherwise shrinks the
+    /// heap buffer.
+    pub fn shrink_to_fit(&mut self) {
+        if let Storage::Heap(buf) = &mut self.data {
+            if self.len > N {
+                buf.shrink_to_fit(self.len);
+                return;
+            }
+        } else {
+            return;
+        }
+
+        let old = mem::replace(&mut self.data, Storage::Inline(MaybeUninit::uninit()));
+        if let Storage::Heap(buf) = &old {
+            unsafe {
+                ptr::copy_nonoverlapping(buf.ptr(), self.as_mut_ptr(), self.len);
+            }
+        }
+        // Frees the heap buffer; RawVec never drops elements
+        drop(old);
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::from_raw(self.as_ptr(), 0, self.len)
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut::from_raw(self.as_mut_ptr(), 0, self.len)
+    }
+
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> iter::Drain<&mut Self> {
+        let range = slice_range(range, self.len);
+        iter::Drain::from_buffer(self, range.start, range.end)
+    }
+
+    /// Keeps only the elements for which `f` returns true, in order.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.retain_mut(|item| f(item));
+    }
+
+    /// Like `retain`, but the predicate may mutate the elements it keeps.
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
+        self.extract_if(.., |item| !f(item)).for_each(drop);
+    }
+
+    /// Removes and yields the elements in `range` for which `filter` returns
+    /// true. Elements not yet visited when the iterator is dropped are kept.
+    pub fn extract_if<F, R>(&mut self, range: R, filter: F) -> iter::ExtractIf<&mut Self, F>
+    where
+        F: FnMut(&mut T) -> bool,
+        R: RangeBounds<usize>,
+    {
+        let range = slice_range(range, self.len);
+        iter::ExtractIf::from_buffer(self, range.start, range.end, filter)
+    }
+
+    /// Removes consecutive elements that map to the same key.
+    pub fn dedup_by_key<F, K>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut T) -> K,
+        K: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    /// Removes consecutive elements for which `same_bucket(current, previous)`
+    /// returns true, keeping the first of each run.
+    pub fn dedup_by<F>(&mut self, same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        self.dedup_in_place(same_bucket);
+    }
+
+    /// Moves all elements of `other` to the end of `self` with a single copy,
+    /// leaving `other` empty.
+    pub fn append(&mut self, other: &mut Self) {
+        let count = other.len;
+        if count == 0 {
+            return;
+        }
+        self.reserve(count);
+        unsafe {
+            ptr::copy_nonoverlapping(other.as_ptr(), self.as_mut_ptr().add(self.len), count);
+            other.len = 0;
+        }
+        self.len += count;
+    }
+
+    /// Splits off the elements from `at` onwards into a new vector, which
+    /// stays inline if they fit.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        if at > self.len {
+            panic!("`at` split index (is {}) should be <= len (is {})", at, self.len);
+        }
+
+        let count = self.len - at;
+        let mut other = SmallVec::with_capacity(count);
+        unsafe {
+            ptr::copy_nonoverlapping(self.as_ptr().add(at), other.as_mut_ptr(), count);
+        }
+        self.len = at;
+        other.len = count;
+        other
+    }
+
+    /// Returns the element or subslice at `index`, or `None` if out of bounds.
+    pub fn get<I: SliceIndex<[T]>>(&self, index: I) -> Option<&I::Output> {
+        self.as_slice().get(index)
+    }
+
+    pub fn get_mut<I: SliceIndex<[T]>>(&mut self, index: I) -> Option<&mut I::Output> {
+        self.as_mut_slice().get_mut(index)
+    }
+
+    /// Returns mutable references to several distinct elements at once.
+    /// Fails if any index is out of bounds or two indices are equal.
+    pub fn get_many_mut<const M: usize>(
+        &mut self,
+        indices: [usize; M],
+    ) -> Result<[&mut T; M], GetManyMutError> {
+        index::get_many_mut(self.as_mut_slice(), indices)
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        self.deref()
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.deref_mut()
+    }
+
+    /// Converts into a `Vec`, reusing the heap buffer if already spilled.
+    pub fn into_vec(self) -> Vec<T> {
+        let mut this = ManuallyDrop::new(self);
+        let len = this.len;
+
+        if let Storage::Heap(buf) = &this.data {
+            // Ownership of the buffer moves to the Vec; `this` is never dropped
+            return Vec {
+                buf: unsafe { ptr::read(buf) },
+                len,
+            };
+        }
+
+        let mut vec = Vec::with_capacity(len);
+        unsafe {
+            ptr::copy_nonoverlapping(this.as_mut_ptr(), vec.buf.ptr(), len);
+        }
+        vec.len = len;
+        vec
+    }
+}
+
+impl<T, const N: usize> Drop for SmallVec<T, N> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.as_mut_ptr(), self.len));
+        }
+        // The heap buffer (if any) is freed by RawVec's Drop
+    }
+}
+
+impl<T, const N: usize> Deref for SmallVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+}
+
+impl<T, const N: usize> DerefMut for SmallVec<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+}
+
+impl<T, I: VecIndex<T>, const N: usize> Index<I> for SmallVec<T, N> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        match self.as_slice().get(index.clone()) {
+            Some(output) => output,
+            None => index.out_of_bounds(self.len),
+        }
+    }
+}
+
+impl<T, I: VecIndex<T>, const N: usize> IndexMut<I> for SmallVec<T, N> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        let len = self.len;
+        match self.as_mut_slice().get_mut(index.clone()) {
+            Some(output) => output,
+            None => index.out_of_bounds(len),
+        }
+    }
+}
+
+impl<T: Clone, const N: usize> SmallVec<T, N> {
+    /// Appends clones of every element in `other`, reserving once up front.
+    pub fn extend_from_slice(&mut self, other: &[T]) {
+        self.extend_cloned(other);
+    }
+
+    /// Resizes to `new_len`, filling new slots with clones of `value`.
+    pub fn resize(&mut self, new_len: usize, value: T) {
+        let len = self.len;
+        if new_len <= len {
+            self.truncate(new_len);
+            return;
+        }
+
+        self.reserve(new_len - len);
+
+        // Clone into all but the last slot, then move `value` into it. len is
+        // bumped per element so a panicking clone leaves a valid vector.
+        let ptr = self.as_mut_ptr();
+        unsafe {
+            for i in len..new_len - 1 {
+                ptr.add(i).write(value.clone());
+                self.len = i + 1;
+            }
+            ptr.add(new_len - 1).write(value);
+        }
+        self.len = new_len;
+    }
+}
+
+impl<T: PartialEq, const N: usize> SmallVec<T, N> {
+    /// Removes consecutive repeated elements.
+    pub fn dedup(&mut self) {
+        self.dedup_by(|a, b| a == b);
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for SmallVec<T, N> {
+    fn clone(&self) -> Self {
+        let mut new_vec = SmallVec::with_capacity(self.len);
+        for item in self.iter() {
+            new_vec.push(item.clone());
+        }
+        new_vec
+    }
+}
+
+// Element-wise, like slices; whether either side has spilled doesn't matter
+impl<T: PartialEq<U>, U, const N: usize, const M: usize> PartialEq<SmallVec<U, M>> for SmallVec<T, N> {
+    fn eq(&self, other: &SmallVec<U, M>) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: PartialEq<U>, U, const N: usize> PartialEq<[U]> for SmallVec<T, N> {
+    fn eq(&self, other: &[U]) -> bool {
+        self[..] == *other
+    }
+}
+
+impl<T: PartialEq<U>, U, const N: usize> PartialEq<&[U]> for SmallVec<T, N> {
+    fn eq(&self, other: &&[U]) -> bool {
+        self[..] == **other
+    }
+}
+
+impl<T: PartialEq<U>, U, const N: usize, const M: usize> PartialEq<[U; M]> for SmallVec<T, N> {
+    fn eq(&self, other: &[U; M]) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for SmallVec<T, N> {}
+
+impl<T: PartialOrd, const N: usize> PartialOrd for SmallVec<T, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        PartialOrd::partial_cmp(&**self, &**other)
+    }
+}
+
+impl<T: Ord, const N: usize> Ord for SmallVec<T, N> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        Ord::cmp(&**self, &**other)
+    }
+}
+
+// Hashes exactly like the equivalent slice
+impl<T: Hash, const N: usize> Hash for SmallVec<T, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+impl<T: std::fmt::Debug, const N: usize> std::fmt::Debug for SmallVec<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const N: usize> Default for SmallVec<T, N> {
+    fn default() -> Self {
+        SmallVec::new()
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for SmallVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = SmallVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T, const N: usize> Extend<T> for SmallVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        self.reserve(lower);
+
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T, const N: usize> IntoIterator for SmallVec<T, N> {
+    type Item = T;
+    type IntoIter = iter::IntoIter<SmallVec<T, N>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        iter::IntoIter::from_buffer(self)
+    }
+}
+
+unsafe impl<T, const N: usize> Buffer for SmallVec<T, N> {
+    type Item = T;
+
+    fn as_ptr(&self) -> *const T {
+        self.as_ptr()
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut T {
+        self.as_mut_ptr()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    unsafe fn set_len(&mut self, len: usize) {
+        self.len = len;
+    }
+
+    fn reserve_total(&mut self, total: usize) {
+        let cap = self.capacity();
+        if total <= cap {
+            return;
+        }
+        match &mut self.data {
+            Storage::Heap(buf) => buf.reserve(total - cap),
+            Storage::Inline(_) => handle_reserve(self.try_spill(total)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_inline_until_full() {
+        let mut vec: SmallVec<i32, 4> = SmallVec::new();
+        for i in 0..4 {
+            vec.push(i);
+        }
+        assert!(!vec.spilled());
+        assert_eq!(vec.capacity(), 4);
+
+        vec.push(4);
+        assert!(vec.spilled());
+        assert!(vec.capacity() >= 5);
+        assert_eq!(vec.as_slice(), &[0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let vec: SmallVec<u8, 8> = SmallVec::with_capacity(8);
+        assert!(!vec.spilled());
+        let vec: SmallVec<u8, 8> = SmallVec::with_capacity(9);
+        assert!(vec.spilled());
+        assert!(vec.capacity() >= 9);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut vec: SmallVec<i32, 2> = SmallVec::new();
+        vec.insert(0, 1);
+        vec.insert(1, 3);
+        vec.insert(1, 2); // spills
+        assert!(vec.spilled());
+        assert_eq!(vec.as_slice(), &[1, 2, 3]);
+        assert_eq!(vec.remove(0), 1);
+        assert_eq!(vec.as_slice(), &[2, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion index (is 3) should be <= len (is 0)")]
+    fn test_insert_out_of_bounds() {
+        let mut vec: SmallVec<i32, 2> = SmallVec::new();
+        vec.insert(3, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "index (is 1) should be < len (is 1)")]
+    fn test_index_out_of_bounds() {
+        let mut vec: SmallVec<i32, 2> = SmallVec::new();
+        vec.push(1);
+        let _ = vec[1];
+    }
+
+    #[test]
+    fn test_range_indexing() {
+        let mut vec: SmallVec<i32, 2> = (0..5).collect();
+        assert_eq!(&vec[1..3], &[1, 2]);
+        vec[3..].reverse();
+        assert_eq!(vec, [0, 1, 2, 4, 3]);
+        assert_eq!(vec.get(..2), Some(&[0, 1][..]));
+        assert_eq!(vec.get(5), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index (is 3) should be <= len (is 1)")]
+    fn test_range_index_out_of_bounds() {
+        let vec: SmallVec<i32, 4> = (0..1).collect();
+        let _ = &vec[..3];
+    }
+
+    #[test]
+    fn test_fallible_api() {
+        let mut vec: SmallVec<u8, 4> = SmallVec::try_with_capacity(4).unwrap();
+        assert!(!vec.spilled());
+        vec.try_extend(0..4).unwrap();
+        vec.try_push(4).unwrap();
+        assert!(vec.spilled());
+        assert_eq!(vec, [0, 1, 2, 3, 4]);
+
+        // A failed reserve leaves the elements where they were
+        let mut inline: SmallVec<u8, 4> = (0..2).collect();
+        assert_eq!(inline.try_reserve(usize::MAX), Err(TryReserveError::CapacityOverflow));
+        assert!(!inline.spilled());
+        assert!(SmallVec::<u64, 4>::try_with_capacity(usize::MAX).is_err());
+        assert!(vec.try_reserve(usize::MAX).is_err());
+        assert_eq!(vec, [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_vec_methods() {
+        let mut vec: SmallVec<i32, 4> = SmallVec::new();
+        vec.extend_from_slice(&[1, 1, 2, 3, 3]);
+        vec.dedup();
+        assert_eq!(vec, [1, 2, 3]);
+        vec.resize(6, 7);
+        assert_eq!(vec, [1, 2, 3, 7, 7, 7]);
+        vec.retain(|&x| x != 2);
+        assert_eq!(vec, [1, 3, 7, 7, 7]);
+
+        let mut tail = vec.split_off(2);
+        assert!(!tail.spilled());
+        assert_eq!((vec.as_slice(), tail.as_slice()), (&[1, 3][..], &[7, 7, 7][..]));
+        tail.append(&mut vec);
+        assert!(vec.is_empty());
+        assert_eq!(tail, [7, 7, 7, 1, 3]);
+
+        let [a, b] = tail.get_many_mut([0, 4]).unwrap();
+        std::mem::swap(a, b);
+        assert_eq!(tail, [3, 7, 7, 1, 7]);
+        assert!(tail.get_many_mut([5]).is_err());
+
+        let mut raw: SmallVec<i32, 4> = SmallVec::new();
+        unsafe {
+            raw.as_mut_ptr().write(9);
+            raw.set_len(1);
+        }
+        assert_eq!(raw, [9]);
+    }
+
+    #[test]
+    fn test_eq_ord_hash() {
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash<T: Hash + ?Sized>(value: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let inline: SmallVec<i32, 8> = (0..3).collect();
+        let spilled: SmallVec<i32, 1> = (0..3).collect();
+        assert_eq!(inline, spilled);
+        assert_eq!(hash(&inline), hash(&[0, 1, 2][..]));
+        assert_eq!(hash(&spilled), hash(&inline));
+
+        let other: SmallVec<i32, 8> = (1..3).collect();
+        assert!(inline < other);
+        assert_eq!(inline.cmp(&inline.clone()), std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn test_shrink_to_fit_moves_back_inline() {
+        let mut vec: SmallVec<String, 2> = SmallVec::new();
+        for i in 0..10 {
+            vec.push(i.to_string());
+        }
+        vec.truncate(2);
+        vec.shrink_to_fit();
+        assert!(!vec.spilled());
+        assert_eq!(vec.as_slice(), &["0", "1"]);
+    }
+
+    #[test]
+    fn test_into_vec() {
+        let inline: SmallVec<i32, 4> = (0..3).collect();
+        let vec = inline.into_vec();
+        assert_eq!(vec.as_slice(), &[0, 1, 2]);
+
+        let spilled: SmallVec<i32, 4> = (0..10).collect();
+        let heap_ptr = spilled.as_ptr();
+        let vec = spilled.into_vec();
+        assert_eq!(vec.len(), 10);
+        // Spilled buffers are handed over without copying
+        assert_eq!(vec.as_slice().as_ptr(), heap_ptr);
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut vec: SmallVec<i32, 4> = (0..3).collect();
+        for x in vec.iter_mut() {
+            *x *= 10;
+        }
+        let collected: std::vec::Vec<_> = vec.iter().rev().copied().collect();
+        assert_eq!(collected, [20, 10, 0]);
+
+        let mut iter = vec.into_iter();
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next_back(), Some(20));
+        assert_eq!(iter.next(), Some(10));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut vec: SmallVec<i32, 4> = (0..6).collect();
+        let drained: std::vec::Vec<_> = vec.drain(1..3).collect();
+        assert_eq!(drained, [1, 2]);
+        assert_eq!(vec.as_slice(), &[0, 3, 4, 5]);
+
+        // Dropping a partially consumed drain still closes the gap
+        let mut drain = vec.drain(0..2);
+        assert_eq!(drain.next(), Some(0));
+        drop(drain);
+        assert_eq!(vec.as_slice(), &[4, 5]);
+    }
+
+    #[test]
+    fn test_iterator_slices_and_keep_rest() {
+        let vec: SmallVec<i32, 2> = (0..4).collect();
+        let mut iter = vec.into_iter();
+        iter.next_back();
+        assert_eq!(iter.as_slice(), &[0, 1, 2]);
+        assert_eq!(iter.len(), 3);
+        iter.by_ref().for_each(drop);
+        assert_eq!(iter.next(), None);
+
+        for len in [3, 6] {
+            let mut vec: SmallVec<i32, 4> = (0..len).collect();
+            let mut drain = vec.drain(..2);
+            assert_eq!(drain.next_back(), Some(1));
+            assert_eq!(drain.as_slice(), &[0]);
+            drain.keep_rest();
+            let expected: std::vec::Vec<i32> = (0..len).filter(|x| *x != 1).collect();
+            assert_eq!(vec.as_slice(), &expected[..]);
+        }
+    }
+
+    #[test]
+    fn test_drops_elements() {
+        let tracker = Rc::new(());
+        {
+            let mut vec: SmallVec<Rc<()>, 2> = SmallVec::new();
+            for _ in 0..5 {
+                vec.push(Rc::clone(&tracker));
+            }
+            vec.truncate(3);
+            assert_eq!(Rc::strong_count(&tracker), 4);
+            let mut iter = vec.into_iter();
+            iter.next();
+            assert_eq!(Rc::strong_count(&tracker), 3);
+        }
+        assert_eq!(Rc::strong_count(&tracker), 1);
+    }
+
+    #[test]
+    fn test_zero_sized() {
+        let mut vec: SmallVec<(), 2> = SmallVec::new();
+        for _ in 0..10 {
+            vec.push(());
+        }
+        assert!(vec.spilled());
+        assert_eq!(vec.len(), 10);
+        assert_eq!(vec.pop(), Some(()));
+    }
+
+    #[test]
+    fn test_clone_and_debug() {
+        let vec: SmallVec<i32, 2> = (0..3).collect();
+        let cloned = vec.clone();
+        assert_eq!(format!("{:?}", cloned), "[0, 1, 2]");
+    }
+}
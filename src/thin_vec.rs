@@ -0,0 +1,1112 @@
+use crate::allocator::{Allocator, Global};
+use crate::buffer::Buffer;
+use crate::error::{GetManyMutError, TryReserveError};
+use crate::growth::{GrowthPolicy, TypeSizeAware};
+use crate::index::{self, VecIndex};
+use crate::range::slice_range;
+use crate::raw_vec::handle_reserve;
+use crate::{iter, Vec};
+use std::alloc::Layout;
+use std::borrow::{Borrow, BorrowMut};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ops::{Deref, DerefMut, Index, IndexMut, Range, RangeBounds};
+use std::ptr::{self, NonNull};
+use std::slice::{self, SliceIndex};
+use std::{cmp, fmt};
+
+// Sits at the start of the allocation, followed by the elements
+#[repr(C)]
+struct Header {
+    len: usize,
+    cap: usize,
+}
+
+// Shared by every empty `ThinVec` that hasn't allocated. It lives in
+// read-only memory and is never written; anything that would change the
+// length allocates a header of its own first.
+static EMPTY_HEADER: Header = Header { len: 0, cap: 0 };
+
+/// A vector that is a single pointer wide. The length and capacity live in a
+/// header in front of the elements, and an empty `ThinVec` points at a shared
+/// static header instead of allocating, so `Option<ThinVec<T>>` is one word
+/// too.
+///
+/// Meant for structs that hold many, mostly empty, vectors. Every access
+/// reads through the pointer to find the length, so `Vec` is the better
+/// choice for hot loops. Always uses the global allocator and the default
+/// growth policy.
+pub struct ThinVec<T> {
+    ptr: NonNull<Header>,
+    _marker: PhantomData<T>,
+}
+
+// Owns its elements like Vec<T>
+unsafe impl<T: Send> Send for ThinVec<T> {}
+unsafe impl<T: Sync> Sync for ThinVec<T> {}
+
+impl<T> ThinVec<T> {
+    pub fn new() -> Self {
+        ThinVec {
+            ptr: NonNull::from(&EMPTY_HEADER),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut vec = Self::new();
+        vec.reserve_exact(capacity);
+        vec
+    }
+
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+        let mut vec = Self::new();
+        vec.try_reserve_exact(capacity)?;
+        Ok(vec)
+    }
+
+    /// Creates a vector of `len` elements whose bytes are all zero, with a
+    /// single allocation and no per-element writes.
+    ///
+    /// # Safety
+    ///
+    /// The all-zero bit pattern must be a valid value of `T`.
+    pub unsafe fn with_len_zeroed(len: usize) -> Self {
+        let mut vec = Self::with_capacity(len);
+        if len > 0 {
+            ptr::write_bytes(vec.data_ptr(), 0, len);
+            vec.set_len(len);
+        }
+        vec
+    }
+
+    // Layout of a header followed by `cap` elements, and the elements' offset
+    fn layout(cap: usize) -> Result<(Layout, usize), TryReserveError> {
+        let elements = Layout::array::<T>(cap).map_err(|_| TryReserveError::CapacityOverflow)?;
+        let (layout, offset) = Layout::new::<Header>()
+            .extend(elements)
+            .map_err(|_| TryReserveError::CapacityOverflow)?;
+        Ok((layout.pad_to_align(), offset))
+    }
+
+    fn is_singleton(&self) -> bool {
+        ptr::eq(self.ptr.as_ptr(), &EMPTY_HEADER)
+    }
+
+    fn header(&self) -> &Header {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    // The static header may not be aligned for T, so an unallocated vector
+    // hands out a dangling pointer like Vec does
+    fn data_ptr(&self) -> *mut T {
+        if self.is_singleton() {
+            return NonNull::dangling().as_ptr();
+        }
+        let (_, offset) = Self::layout(0).expect("layout overflow");
+        unsafe { (self.ptr.as_ptr() as *mut u8).add(offset) as *mut T }
+    }
+
+    pub fn len(&self) -> usize {
+        self.header().len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Zero until the first allocation; `usize::MAX` afterwards for
+    /// zero-sized types.
+    pub fn capacity(&self) -> usize {
+        self.header().cap
+    }
+
+    /// Returns a pointer to the elements. Never null: an unallocated vector
+    /// returns a dangling, well-aligned pointer.
+    pub fn as_ptr(&self) -> *const T {
+        self.data_ptr()
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.data_ptr()
+    }
+
+    /// Sets the length without touching the elements.
+    ///
+    /// # Safety
+    ///
+    /// `new_len` must be at most `capacity()`, and the elements up to
+    /// `new_len` must be initialized.
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        debug_assert!(new_len <= self.capacity());
+        // The shared empty header already says 0 and must not be written
+        if !self.is_singleton() {
+            (*self.ptr.as_ptr()).len = new_len;
+        }
+    }
+
+    /// Returns the unused capacity as uninitialized slots. Write into them,
+    /// then call `set_len` to make them part of the vector.
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        let (len, spare) = (self.len(), self.capacity() - self.len());
+        unsafe {
+            slice::from_raw_parts_mut(self.as_mut_ptr().add(len) as *mut MaybeUninit<T>, spare)
+        }
+    }
+
+    /// Makes room for at least `additional` more elements past the current
+    /// length, growing by the default policy.
+    pub fn reserve(&mut self, additional: usize) {
+        handle_reserve(self.try_reserve(additional));
+    }
+
+    pub fn reserve_exact(&mut self, additional: usize) {
+        handle_reserve(self.try_reserve_exact(additional));
+    }
+
+    /// Fallible counterpart of `reserve`: reports capacity overflow or
+    /// allocator failure instead of panicking or aborting.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.try_reserve_inner(additional, true)
+    }
+
+    /// Like `try_reserve`, but grows capacity to exactly `len + additional`
+    /// without applying the growth strategy.
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.try_reserve_inner(additional, false)
+    }
+
+    fn try_reserve_inner(&mut self, additional: usize, amortized: bool) -> Result<(), TryReserveError> {
+        let (len, cap) = (self.len(), self.capacity());
+        let required = len.checked_add(additional).ok_or(TryReserveError::CapacityOverflow)?;
+        if required <= cap {
+            return Ok(());
+        }
+
+        // Zero-sized elements take no room, so the first header is the last
+        let elem_size = std::mem::size_of::<T>();
+        if elem_size == 0 {
+            return self.try_grow_to(usize::MAX);
+        }
+
+        let max_elements = isize::MAX as usize / elem_size;
+        if required > max_elements {
+            return Err(TryReserveError::CapacityOverflow);
+        }
+        let new_cap = if amortized {
+            let grown = TypeSizeAware.grow(cap, required, elem_size);
+            cmp::min(cmp::max(grown, required), max_elements)
+        } else {
+            required
+        };
+        self.try_grow_to(new_cap)
+    }
+
+    fn try_grow_to(&mut self, new_cap: usize) -> Result<(), TryReserveError> {
+        let (new_layout, _) = Self::layout(new_cap)?;
+        let singleton = self.is_singleton();
+        let result = if singleton {
+            Global.allocate(new_layout)
+        } else {
+            // The whole old block is copied, so a tail parked past `len` by
+            // Drain or Splice survives the move
+            let (old_layout, _) = Self::layout(self.capacity()).expect("old layout overflow");
+            unsafe { Global.grow(self.ptr.cast(), old_layout, new_layout) }
+        };
+
+        // On failure the old allocation is untouched and still ours
+        let ptr = result
+            .map_err(|_| TryReserveError::AllocError { layout: new_layout })?
+            .cast::<Header>();
+        unsafe {
+            if singleton {
+                ptr.as_ptr().write(Header { len: 0, cap: new_cap });
+            } else {
+                (*ptr.as_ptr()).cap = new_cap;
+            }
+        }
+        self.ptr = ptr;
+        Ok(())
+    }
+
+    /// Shrinks the allocation to fit the length. An empty vector frees its
+    /// header and goes back to the shared one.
+    pub fn shrink_to_fit(&mut self) {
+        if self.is_singleton() {
+            return;
+        }
+        let (len, cap) = (self.len(), self.capacity());
+        if len == 0 {
+            unsafe { self.deallocate() };
+            self.ptr = NonNull::from(&EMPTY_HEADER);
+            return;
+        }
+        if std::mem::size_of::<T>() == 0 || len == cap {
+            return;
+        }
+
+        let (old_layout, _) = Self::layout(cap).expect("old layout overflow");
+        let (new_layout, _) = Self::layout(len).expect("layout overflow");
+
+        // Shrinking is best-effort: keep the old block on failure
+        if let Ok(ptr) = unsafe { Global.shrink(self.ptr.cast(), old_layout, new_layout) } {
+            self.ptr = ptr.cast();
+            unsafe { (*self.ptr.as_ptr()).cap = len };
+        }
+    }
+
+    // Frees the allocation without dropping the elements
+    unsafe fn deallocate(&self) {
+        debug_assert!(!self.is_singleton());
+        let (layout, _) = Self::layout(self.capacity()).expect("layout overflow");
+        Global.deallocate(self.ptr.cast(), layout);
+    }
+
+    pub fn push(&mut self, item: T) {
+        let len = self.len();
+        if len == self.capacity() {
+            // When at capacity, the growth policy decides how far to grow
+            self.reserve(1);
+        }
+        unsafe {
+            self.data_ptr().add(len).write(item);
+            self.set_len(len + 1);
+        }
+    }
+
+    /// Pushes `item`, returning an error (and dropping `item`) if the
+    /// vector needed to grow and the allocation failed.
+    pub fn try_push(&mut self, item: T) -> Result<(), TryReserveError> {
+        let len = self.len();
+        if len == self.capacity() {
+            self.try_reserve(1)?;
+        }
+        unsafe {
+            self.data_ptr().add(len).write(item);
+            self.set_len(len + 1);
+        }
+        Ok(())
+    }
+
+    /// Fallible counterpart of `extend`. Stops at the first failed
+    /// allocation; elements pushed before the failure are kept.
+    pub fn try_extend<I: IntoIterator<Item = T>>(&mut self, iter: I) -> Result<(), TryReserveError> {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        self.try_reserve(lower)?;
+
+        for item in iter {
+            self.try_push(item)?;
+        }
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let len = self.len();
+        if len == 0 {
+            return None;
+        }
+        unsafe {
+            self.set_len(len - 1);
+            Some(self.data_ptr().add(len - 1).read())
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, item: T) {
+        let len = self.len();
+        if index > len {
+            panic!("insertion index (is {}) should be <= len (is {})", index, len);
+        }
+        if len == self.capacity() {
+            self.reserve(1);
+        }
+        unsafe { self.insert_unchecked(index, item) };
+    }
+
+    pub fn try_insert(&mut self, index: usize, item: T) -> Result<(), TryReserveError> {
+        let len = self.len();
+        if index > len {
+            panic!("insertion index (is {}) should be <= len (is {})", index, len);
+        }
+        if len == self.capacity() {
+            self.try_reserve(1)?;
+        }
+        unsafe { self.insert_unchecked(index, item) };
+        Ok(())
+    }
+
+    // Caller guarantees `index <= len < capacity`
+    unsafe fn insert_unchecked(&mut self, index: usize, item: T) {
+        let len = self.len();
+        let ptr = self.data_ptr().add(index);
+        ptr::copy(ptr, ptr.add(1), len - index);
+        ptr.write(item);
+        self.set_len(len + 1);
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len();
+        if index >= len {
+            panic!("removal index (is {}) should be < len (is {})", index, len);
+        }
+        unsafe {
+            let ptr = self.data_ptr().add(index);
+            let item = ptr.read();
+            ptr::copy(ptr.add(1), ptr, len - index - 1);
+            self.set_len(len - 1);
+            item
+        }
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        self.drop_tail(len);
+    }
+
+    pub fn clear(&mut self) {
+        // Drop all elements but keep the allocation
+        self.truncate(0);
+    }
+
+    /// Resizes to `new_len`, filling new slots with values returned by `f`.
+    pub fn resize_with<F: FnMut() -> T>(&mut self, new_len: usize, mut f: F) {
+        let len = self.len();
+        if new_len <= len {
+            self.truncate(new_len);
+            return;
+        }
+
+        self.reserve(new_len - len);
+
+        // Bump len per element so a panicking closure leaves a valid vector
+        let ptr = self.data_ptr();
+        for i in len..new_len {
+            unsafe {
+                ptr.add(i).write(f());
+                self.set_len(i + 1);
+            }
+        }
+    }
+
+    pub fn iter(&self) -> iter::Iter<'_, T> {
+        iter::Iter::from_raw(self.as_ptr(), 0, self.len())
+    }
+
+    pub fn iter_mut(&mut self) -> iter::IterMut<'_, T> {
+        let len = self.len();
+        iter::IterMut::from_raw(self.as_mut_ptr(), 0, len)
+    }
+
+    /// Removes the elements in `range` and yields them by value. Whatever the
+    /// iterator doesn't consume is dropped when it goes out of scope.
+    ///
+    /// Panics if the start of the range is after its end, or the end is
+    /// past the length of the vector.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> iter::Drain<&mut Self> {
+        let Range { start, end } = slice_range(range, self.len());
+        iter::Drain::from_buffer(self, start, end)
+    }
+
+    /// Replaces the elements in `range` with the contents of `replace_with`
+    /// and yields the removed elements. The replacement happens when the
+    /// returned `Splice` is dropped.
+    ///
+    /// Panics under the same conditions as `drain`.
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> iter::Splice<&mut Self, I::IntoIter>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        iter::Splice::new(self.drain(range), replace_with.into_iter())
+    }
+
+    /// Keeps only the elements for which `f` returns true, in order.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.retain_mut(|item| f(item));
+    }
+
+    /// Like `retain`, but the predicate may mutate the elements it keeps.
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
+        self.extract_if(.., |item| !f(item)).for_each(drop);
+    }
+
+    /// Removes and yields the elements in `range` for which `filter` returns
+    /// true. Elements not yet visited when the iterator is dropped are kept.
+    ///
+    /// Panics under the same conditions as `drain`.
+    pub fn extract_if<F, R>(&mut self, range: R, filter: F) -> iter::ExtractIf<&mut Self, F>
+    where
+        F: FnMut(&mut T) -> bool,
+        R: RangeBounds<usize>,
+    {
+        let Range { start, end } = slice_range(range, self.len());
+        iter::ExtractIf::from_buffer(self, start, end, filter)
+    }
+
+    /// Removes consecutive elements that map to the same key.
+    pub fn dedup_by_key<F, K>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut T) -> K,
+        K: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    /// Removes consecutive elements for which `same_bucket(current, previous)`
+    /// returns true, keeping the first of each run. Compacts in place and
+    /// never reallocates.
+    pub fn dedup_by<F>(&mut self, same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        self.dedup_in_place(same_bucket);
+    }
+
+    /// Moves all elements of `other` to the end of `self` with a single copy,
+    /// leaving `other` empty but with its capacity intact.
+    pub fn append(&mut self, other: &mut ThinVec<T>) {
+        let count = other.len();
+        if count == 0 {
+            return;
+        }
+        self.reserve(count);
+        let len = self.len();
+        unsafe {
+            ptr::copy_nonoverlapping(other.data_ptr(), self.data_ptr().add(len), count);
+            other.set_len(0);
+            self.set_len(len + count);
+        }
+    }
+
+    /// Splits off the elements from `at` onwards into a new vector.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let len = self.len();
+        if at > len {
+            panic!("`at` split index (is {}) should be <= len (is {})", at, len);
+        }
+
+        let count = len - at;
+        let mut other = ThinVec::with_capacity(count);
+        unsafe {
+            ptr::copy_nonoverlapping(self.data_ptr().add(at), other.data_ptr(), count);
+            self.set_len(at);
+            other.set_len(count);
+        }
+        other
+    }
+
+    /// Returns the element or subslice at `index`, or `None` if out of bounds.
+    pub fn get<I: SliceIndex<[T]>>(&self, index: I) -> Option<&I::Output> {
+        self.as_slice().get(index)
+    }
+
+    pub fn get_mut<I: SliceIndex<[T]>>(&mut self, index: I) -> Option<&mut I::Output> {
+        self.as_mut_slice().get_mut(index)
+    }
+
+    /// Returns mutable references to several distinct elements at once.
+    /// Fails if any index is out of bounds or two indices are equal.
+    pub fn get_many_mut<const N: usize>(
+        &mut self,
+        indices: [usize; N],
+    ) -> Result<[&mut T; N], GetManyMutError> {
+        index::get_many_mut(self.as_mut_slice(), indices)
+    }
+
+    pub fn first_chunk<const N: usize>(&self) -> Option<&[T; N]> {
+        self.as_slice().first_chunk()
+    }
+
+    pub fn first_chunk_mut<const N: usize>(&mut self) -> Option<&mut [T; N]> {
+        self.as_mut_slice().first_chunk_mut()
+    }
+
+    pub fn last_chunk<const N: usize>(&self) -> Option<&[T; N]> {
+        self.as_slice().last_chunk()
+    }
+
+    pub fn last_chunk_mut<const N: usize>(&mut self) -> Option<&mut [T; N]> {
+        self.as_mut_slice().last_chunk_mut()
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+
+    /// Consumes the vector and returns its contents as a slice that lives
+    /// for as long as needed. The allocation is never freed.
+    pub fn leak<'a>(self) -> &'a mut [T] {
+        let mut this = ManuallyDrop::new(self);
+        let len = this.len();
+        unsafe { slice::from_raw_parts_mut(this.as_mut_ptr(), len) }
+    }
+}
+
+impl<T: Clone> ThinVec<T> {
+    /// Creates a vector holding `n` clones of `elem`.
+    pub fn from_elem(elem: T, n: usize) -> Self {
+        let mut vec = ThinVec::with_capacity(n);
+        vec.resize(n, elem);
+        vec
+    }
+
+    /// Appends clones of every element in `other`, reserving once up front.
+    pub fn extend_from_slice(&mut self, other: &[T]) {
+        self.extend_cloned(other);
+    }
+
+    /// Resizes to `new_len`, filling new slots with clones of `value`.
+    pub fn resize(&mut self, new_len: usize, value: T) {
+        let len = self.len();
+        if new_len <= len {
+            self.truncate(new_len);
+            return;
+        }
+
+        self.reserve(new_len - len);
+
+        // Clone into all but the last slot, then move `value` into it. len is
+        // bumped per element so a panicking clone leaves a valid vector.
+        let ptr = self.data_ptr();
+        unsafe {
+            for i in len..new_len - 1 {
+                ptr.add(i).write(value.clone());
+                self.set_len(i + 1);
+            }
+            ptr.add(new_len - 1).write(value);
+            self.set_len(new_len);
+        }
+    }
+
+    /// Appends clones of the elements in `range` to the end of the vector.
+    ///
+    /// Panics under the same conditions as `drain`.
+    pub fn extend_from_within<R: RangeBounds<usize>>(&mut self, range: R) {
+        let range = slice_range(range, self.len());
+        self.extend_cloned_within(range);
+    }
+}
+
+impl<T: PartialEq> ThinVec<T> {
+    /// Removes consecutive repeated elements.
+    pub fn dedup(&mut self) {
+        self.dedup_by(|a, b| a == b);
+    }
+}
+
+impl<T> Drop for ThinVec<T> {
+    fn drop(&mut self) {
+        if self.is_singleton() {
+            return;
+        }
+
+        // Free the allocation even if an element's destructor panics
+        struct Deallocate<'a, T>(&'a ThinVec<T>);
+
+        impl<T> Drop for Deallocate<'_, T> {
+            fn drop(&mut self) {
+                unsafe { self.0.deallocate() };
+            }
+        }
+
+        let guard = Deallocate(self);
+        if std::mem::needs_drop::<T>() {
+            unsafe {
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(guard.0.data_ptr(), guard.0.len()));
+            }
+        }
+    }
+}
+
+impl<T> Deref for ThinVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.data_ptr(), self.len()) }
+    }
+}
+
+impl<T> DerefMut for ThinVec<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.data_ptr(), self.len()) }
+    }
+}
+
+impl<T, I: VecIndex<T>> Index<I> for ThinVec<T> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        match self.as_slice().get(index.clone()) {
+            Some(output) => output,
+            None => index.out_of_bounds(self.len()),
+        }
+    }
+}
+
+impl<T, I: VecIndex<T>> IndexMut<I> for ThinVec<T> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        let len = self.len();
+        match self.as_mut_slice().get_mut(index.clone()) {
+            Some(output) => output,
+            None => index.out_of_bounds(len),
+        }
+    }
+}
+
+impl<T: Clone> Clone for ThinVec<T> {
+    fn clone(&self) -> Self {
+        let mut vec = ThinVec::with_capacity(self.len());
+        vec.extend_from_slice(self);
+        vec
+    }
+
+    // Reuses the existing allocation and elements where possible
+    fn clone_from(&mut self, source: &Self) {
+        self.assign_cloned(source);
+    }
+}
+
+impl<T> Default for ThinVec<T> {
+    fn default() -> Self {
+        ThinVec::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ThinVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Extend<T> for ThinVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        self.reserve(lower);
+
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for ThinVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = ThinVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T> IntoIterator for ThinVec<T> {
+    type Item = T;
+    type IntoIter = iter::IntoIter<ThinVec<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        iter::IntoIter::from_buffer(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ThinVec<T> {
+    type Item = &'a T;
+    type IntoIter = iter::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut ThinVec<T> {
+    type Item = &'a mut T;
+    type IntoIter = iter::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// Moves the elements with one memcpy; neither side clones
+impl<T, A: Allocator, G: GrowthPolicy> From<Vec<T, A, G>> for ThinVec<T> {
+    fn from(mut vec: Vec<T, A, G>) -> Self {
+        let len = vec.len();
+        let mut thin = ThinVec::with_capacity(len);
+        unsafe {
+            ptr::copy_nonoverlapping(vec.as_ptr(), thin.as_mut_ptr(), len);
+            vec.set_len(0);
+            thin.set_len(len);
+        }
+        thin
+    }
+}
+
+impl<T> From<ThinVec<T>> for Vec<T> {
+    fn from(mut thin: ThinVec<T>) -> Self {
+        let len = thin.len();
+        let mut vec = Vec::with_capacity(len);
+        unsafe {
+            ptr::copy_nonoverlapping(thin.as_ptr(), vec.as_mut_ptr(), len);
+            thin.set_len(0);
+            vec.set_len(len);
+        }
+        vec
+    }
+}
+
+impl<T: Clone> From<&[T]> for ThinVec<T> {
+    fn from(slice: &[T]) -> Self {
+        let mut vec = ThinVec::with_capacity(slice.len());
+        vec.extend_from_slice(slice);
+        vec
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for ThinVec<T> {
+    fn from(array: [T; N]) -> Self {
+        let array = ManuallyDrop::new(array);
+        let mut vec = ThinVec::with_capacity(N);
+        unsafe {
+            ptr::copy_nonoverlapping(array.as_ptr(), vec.as_mut_ptr(), N);
+            vec.set_len(N);
+        }
+        vec
+    }
+}
+
+// Element-wise equality against the other vectors and slices, in the
+// directions std's Vec supports
+impl<T: PartialEq<U>, U> PartialEq<ThinVec<U>> for ThinVec<T> {
+    fn eq(&self, other: &ThinVec<U>) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: PartialEq<U>, U, A: Allocator, G: GrowthPolicy> PartialEq<Vec<U, A, G>> for ThinVec<T> {
+    fn eq(&self, other: &Vec<U, A, G>) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: PartialEq<U>, U> PartialEq<[U]> for ThinVec<T> {
+    fn eq(&self, other: &[U]) -> bool {
+        self[..] == *other
+    }
+}
+
+impl<T: PartialEq<U>, U> PartialEq<&[U]> for ThinVec<T> {
+    fn eq(&self, other: &&[U]) -> bool {
+        self[..] == **other
+    }
+}
+
+impl<T: PartialEq<U>, U, const N: usize> PartialEq<[U; N]> for ThinVec<T> {
+    fn eq(&self, other: &[U; N]) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: PartialEq<U>, U, const N: usize> PartialEq<&[U; N]> for ThinVec<T> {
+    fn eq(&self, other: &&[U; N]) -> bool {
+        self[..] == other[..]
+    }
+}
+
+// The reverse directions, so `vec == thin` works as well as `thin == vec`
+impl<T: PartialEq<U>, U, A: Allocator, G: GrowthPolicy> PartialEq<ThinVec<U>> for Vec<T, A, G> {
+    fn eq(&self, other: &ThinVec<U>) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: PartialEq<U>, U> PartialEq<ThinVec<U>> for [T] {
+    fn eq(&self, other: &ThinVec<U>) -> bool {
+        *self == other[..]
+    }
+}
+
+impl<T: PartialEq<U>, U> PartialEq<ThinVec<U>> for &[T] {
+    fn eq(&self, other: &ThinVec<U>) -> bool {
+        **self == other[..]
+    }
+}
+
+impl<T: PartialEq<U>, U, const N: usize> PartialEq<ThinVec<U>> for [T; N] {
+    fn eq(&self, other: &ThinVec<U>) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: Eq> Eq for ThinVec<T> {}
+
+// Lexicographic, like slices
+impl<T: PartialOrd> PartialOrd for ThinVec<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        PartialOrd::partial_cmp(&**self, &**other)
+    }
+}
+
+impl<T: Ord> Ord for ThinVec<T> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        Ord::cmp(&**self, &**other)
+    }
+}
+
+// Hashes exactly like the equivalent slice, as Borrow<[T]> requires
+impl<T: Hash> Hash for ThinVec<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Hash::hash(&**self, state)
+    }
+}
+
+impl<T> Borrow<[T]> for ThinVec<T> {
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T> BorrowMut<[T]> for ThinVec<T> {
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T> AsRef<[T]> for ThinVec<T> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T> AsMut<[T]> for ThinVec<T> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+unsafe impl<T> Buffer for ThinVec<T> {
+    type Item = T;
+
+    fn as_ptr(&self) -> *const T {
+        self.data_ptr()
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut T {
+        self.data_ptr()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    unsafe fn set_len(&mut self, len: usize) {
+        self.set_len(len);
+    }
+
+    // `reserve` counts from `len`; the tail past it is kept by `try_grow_to`
+    fn reserve_total(&mut self, total: usize) {
+        if total > self.capacity() {
+            self.reserve(total - self.len());
+        }
+    }
+}
+
+impl<T: Clone> Clone for iter::IntoIter<ThinVec<T>> {
+    // Clones the remaining elements into a fresh vector
+    fn clone(&self) -> Self {
+        ThinVec::from(self.as_slice()).into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::size_of;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_one_word_and_no_allocation_when_empty() {
+        assert_eq!(size_of::<ThinVec<u64>>(), size_of::<usize>());
+        assert_eq!(size_of::<Option<ThinVec<u64>>>(), size_of::<usize>());
+
+        let a: ThinVec<u64> = ThinVec::new();
+        let b: ThinVec<String> = ThinVec::with_capacity(0);
+        assert!(a.is_singleton() && b.is_singleton());
+        assert_eq!(a.capacity(), 0);
+        assert_eq!(a.as_slice(), &[] as &[u64]);
+    }
+
+    #[test]
+    fn test_push_grow_and_shrink_back_to_shared_header() {
+        let mut vec = ThinVec::new();
+        for i in 0..100 {
+            vec.push(i);
+        }
+        assert_eq!(vec.len(), 100);
+        assert!(vec.capacity() >= 100);
+        assert!(vec.iter().copied().eq(0..100));
+
+        vec.truncate(10);
+        vec.shrink_to_fit();
+        assert_eq!(vec.capacity(), 10);
+        assert_eq!(vec, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        vec.clear();
+        vec.shrink_to_fit();
+        assert!(vec.is_singleton());
+        assert_eq!(vec.pop(), None);
+    }
+
+    #[test]
+    fn test_insert_remove_and_edits() {
+        let mut vec: ThinVec<i32> = ThinVec::from([1, 2, 4]);
+        vec.insert(2, 3);
+        vec.insert(0, 0);
+        assert_eq!(vec.remove(4), 4);
+        vec.extend_from_slice(&[3, 3, 5]);
+        vec.dedup();
+        assert_eq!(vec, [0, 1, 2, 3, 5]);
+
+        vec.retain(|x| x % 2 == 1);
+        vec.resize(4, 7);
+        vec.extend_from_within(..1);
+        assert_eq!(vec, [1, 3, 5, 7, 1]);
+
+        let tail = vec.split_off(3);
+        let mut other = ThinVec::from(&[9][..]);
+        vec.append(&mut other);
+        assert_eq!((vec.as_slice(), tail.as_slice()), (&[1, 3, 5, 9][..], &[7, 1][..]));
+        assert!(other.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion index (is 2) should be <= len (is 0)")]
+    fn test_insert_out_of_bounds() {
+        ThinVec::new().insert(2, 'x');
+    }
+
+    #[test]
+    #[should_panic(expected = "index (is 3) should be < len (is 3)")]
+    fn test_index_out_of_bounds() {
+        let vec: ThinVec<i32> = (0..3).collect();
+        let _ = vec[3];
+    }
+
+    #[test]
+    fn test_drain_splice_and_extract_if() {
+        let mut vec: ThinVec<i32> = (0..10).collect();
+        let drained: ThinVec<i32> = vec.drain(2..5).collect();
+        assert_eq!(drained, [2, 3, 4]);
+        assert_eq!(vec, [0, 1, 5, 6, 7, 8, 9]);
+
+        // Growing splice with a size_hint that undercounts
+        let removed: ThinVec<i32> = vec.splice(1..3, (20..30).filter(|x| x % 3 == 0)).collect();
+        assert_eq!(removed, [1, 5]);
+        assert_eq!(vec, [0, 21, 24, 27, 6, 7, 8, 9]);
+
+        let odd: ThinVec<i32> = vec.extract_if(.., |x| *x % 2 == 1).collect();
+        assert_eq!(odd, [21, 27, 7, 9]);
+        assert_eq!(vec, [0, 24, 6, 8]);
+
+        let mut drain = vec.drain(1..3);
+        assert_eq!(drain.next(), Some(24));
+        drain.keep_rest();
+        assert_eq!(vec, [0, 6, 8]);
+    }
+
+    #[test]
+    fn test_extend_ignores_upper_bound() {
+        // The upper bound is u32::MAX, but only three items pass the predicate
+        let vec: ThinVec<u32> = (0..u32::MAX).take_while(|x| *x < 3).collect();
+        assert_eq!(vec, [0, 1, 2]);
+        assert!(vec.capacity() < 1024);
+    }
+
+    #[test]
+    fn test_splice_from_empty() {
+        let mut vec: ThinVec<String> = ThinVec::new();
+        vec.splice(.., ["a", "b"].map(String::from));
+        assert_eq!(vec, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_over_aligned_and_zero_sized() {
+        #[repr(align(64))]
+        #[derive(Clone, Copy, PartialEq, Debug)]
+        struct Aligned(u8);
+
+        let mut vec = ThinVec::new();
+        assert_eq!(vec.as_ptr() as usize % 64, 0);
+        vec.extend((0..20).map(Aligned));
+        assert_eq!(vec.as_ptr() as usize % 64, 0);
+        assert_eq!(vec[19], Aligned(19));
+
+        let mut units: ThinVec<()> = ThinVec::new();
+        assert_eq!(units.capacity(), 0);
+        units.extend(std::iter::repeat_n((), 5));
+        assert_eq!(units.len(), 5);
+        assert_eq!(units.capacity(), usize::MAX);
+        assert_eq!(units.into_iter().count(), 5);
+    }
+
+    #[test]
+    fn test_into_iter_and_conversions() {
+        let vec: ThinVec<String> = ["a", "b", "c", "d"].map(String::from).into();
+        let mut iter = vec.clone().into_iter();
+        assert_eq!(iter.next().as_deref(), Some("a"));
+        assert_eq!(iter.next_back().as_deref(), Some("d"));
+        assert_eq!(iter.as_slice(), ["b", "c"]);
+        let copy = iter.clone();
+        drop(iter);
+        assert_eq!(copy.len(), 2);
+
+        let back: Vec<String> = vec.clone().into();
+        assert_eq!(vec, back);
+        assert_eq!(back, vec);
+        assert_eq!(ThinVec::from(back), vec);
+        let later: ThinVec<String> = ThinVec::from(["b".to_string()]);
+        assert!(vec < later);
+        assert_eq!(["a", "b", "c", "d"].map(String::from), vec);
+    }
+
+    #[test]
+    fn test_constructors_and_fallible_api() {
+        let mut vec: ThinVec<u32> = ThinVec::try_with_capacity(4).unwrap();
+        assert_eq!(vec.capacity(), 4);
+        vec.try_extend(0..6).unwrap();
+        assert_eq!(vec, [0, 1, 2, 3, 4, 5]);
+        assert!(matches!(
+            ThinVec::<u64>::try_with_capacity(usize::MAX),
+            Err(TryReserveError::CapacityOverflow)
+        ));
+
+        assert_eq!(vec.first_chunk::<2>(), Some(&[0, 1]));
+        vec.last_chunk_mut::<2>().unwrap().fill(9);
+        assert_eq!(vec.last_chunk::<3>(), Some(&[3, 9, 9]));
+        assert_eq!(vec.first_chunk_mut::<7>(), None);
+
+        let zeroed: ThinVec<f32> = unsafe { ThinVec::with_len_zeroed(3) };
+        assert_eq!(zeroed, [0.0; 3]);
+        assert_eq!(ThinVec::from_elem("x", 2), ["x", "x"]);
+        assert!(ThinVec::from_elem(1u8, 0).capacity() == 0);
+
+        let leaked: &'static mut [u32] = vec.leak();
+        leaked[0] = 7;
+        assert_eq!(leaked, &[7, 1, 2, 3, 9, 9]);
+    }
+
+    #[test]
+    fn test_drops_elements_once() {
+        let marker = Rc::new(());
+        let mut vec: ThinVec<Rc<()>> = (0..6).map(|_| marker.clone()).collect();
+        drop(vec.drain(1..3));
+        vec.truncate(2);
+        assert_eq!(Rc::strong_count(&marker), 3);
+
+        let mut iter = vec.into_iter();
+        drop(iter.next());
+        drop(iter);
+        assert_eq!(Rc::strong_count(&marker), 1);
+    }
+}
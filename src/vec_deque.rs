@@ -0,0 +1,905 @@
+use crate::allocator::{Allocator, Global};
+use crate::error::TryReserveError;
+use crate::growth::{GrowthPolicy, TypeSizeAware};
+use crate::range::slice_range;
+use crate::raw_vec::RawVec;
+use crate::Vec;
+use std::iter::FusedIterator;
+use std::mem::ManuallyDrop;
+use std::ops::{Index, IndexMut, RangeBounds};
+use std::{fmt, ptr, slice};
+
+/// A double-ended queue in a ring buffer. Shares `RawVec`, and with it the
+/// allocator and growth policy, with `Vec`.
+///
+/// The live elements start at `head` and may wrap past the end of the buffer
+/// back to index 0.
+pub struct VecDeque<T, A: Allocator = Global, G: GrowthPolicy = TypeSizeAware> {
+    buf: RawVec<T, A, G>,
+    head: usize,
+    len: usize,
+}
+
+impl<T> VecDeque<T> {
+    pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+}
+
+impl<T, A: Allocator> VecDeque<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_policy_in(TypeSizeAware, alloc)
+    }
+
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        VecDeque { buf: RawVec::with_capacity_in(capacity, alloc), head: 0, len: 0 }
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> VecDeque<T, A, G> {
+    pub fn with_policy_in(policy: G, alloc: A) -> Self {
+        VecDeque { buf: RawVec::with_policy_in(policy, alloc), head: 0, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.buf.allocator()
+    }
+
+    // RawVec leaves the pointer null until the first allocation
+    fn ptr(&self) -> *mut T {
+        let ptr = self.buf.ptr();
+        if ptr.is_null() {
+            ptr::NonNull::dangling().as_ptr()
+        } else {
+            ptr
+        }
+    }
+
+    // `index + offset` wrapped into the buffer; valid for offset <= capacity.
+    // Overflow only happens with the usize::MAX capacity of ZSTs.
+    fn wrap_add(&self, index: usize, offset: usize) -> usize {
+        let (sum, overflowed) = index.overflowing_add(offset);
+        if overflowed || sum >= self.capacity() {
+            sum.wrapping_sub(self.capacity())
+        } else {
+            sum
+        }
+    }
+
+    fn wrap_sub(&self, index: usize, offset: usize) -> usize {
+        if offset > index {
+            index + (self.capacity() - offset)
+        } else {
+            index - offset
+        }
+    }
+
+    // Buffer slot of the element at logical `index`
+    fn to_physical(&self, index: usize) -> usize {
+        self.wrap_add(self.head, index)
+    }
+
+    // Whether the live elements fit in [head, head + len) without wrapping
+    fn is_contiguous(&self) -> bool {
+        self.head <= self.capacity() - self.len
+    }
+
+    /// Reserves room for at least `additional` more elements, growing at
+    /// most once.
+    pub fn reserve(&mut self, additional: usize) {
+        let spare = self.capacity() - self.len;
+        if additional > spare {
+            let old_cap = self.capacity();
+            self.buf.reserve(additional - spare);
+            self.handle_capacity_increase(old_cap);
+        }
+    }
+
+    /// Fallible counterpart of `reserve`.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let spare = self.capacity() - self.len;
+        if additional > spare {
+            let old_cap = self.capacity();
+            self.buf.try_reserve(additional - spare)?;
+            self.handle_capacity_increase(old_cap);
+        }
+        Ok(())
+    }
+
+    // After the buffer grew from `old_cap`, a ring that wrapped around the
+    // old end has its two halves in the wrong places. Moves whichever half
+    // is cheaper, with a single copy:
+    //
+    //   [D E . . A B C]          old ring, head at A
+    //   [. . . . A B C D E . .]  tail moved past the old end, or
+    //   [D E . . . . . . A B C]  head moved to the new end
+    fn handle_capacity_increase(&mut self, old_cap: usize) {
+        let new_cap = self.capacity();
+        if std::mem::size_of::<T>() == 0 || self.head <= old_cap - self.len {
+            return;
+        }
+
+        let head_len = old_cap - self.head;
+        let tail_len = self.len - head_len;
+        unsafe {
+            if tail_len < head_len && tail_len <= new_cap - old_cap {
+                ptr::copy_nonoverlapping(self.ptr(), self.ptr().add(old_cap), tail_len);
+            } else {
+                let new_head = new_cap - head_len;
+                ptr::copy(self.ptr().add(self.head), self.ptr().add(new_head), head_len);
+                self.head = new_head;
+            }
+        }
+    }
+
+    fn grow_if_full(&mut self) {
+        if self.len == self.capacity() {
+            let old_cap = self.capacity();
+            self.buf.reserve(1);
+            self.handle_capacity_increase(old_cap);
+        }
+    }
+
+    pub fn push_back(&mut self, item: T) {
+        self.grow_if_full();
+        let slot = self.to_physical(self.len);
+        self.buf.write_at(slot, item);
+        self.len += 1;
+    }
+
+    pub fn push_front(&mut self, item: T) {
+        self.grow_if_full();
+        self.head = self.wrap_sub(self.head, 1);
+        self.buf.write_at(self.head, item);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let item = self.buf.read_at(self.head);
+        self.head = self.wrap_add(self.head, 1);
+        self.len -= 1;
+        Some(item)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(self.buf.read_at(self.to_physical(self.len)))
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            Some(self.buf.get_ref(self.to_physical(index)))
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len {
+            let slot = self.to_physical(index);
+            Some(self.buf.get_mut(slot))
+        } else {
+            None
+        }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|last| self.get(last))
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.len.checked_sub(1).and_then(|last| self.get_mut(last))
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        // pop_back shortens the deque before each drop, so a panicking
+        // destructor can't cause a double drop
+        while self.len > len {
+            drop(self.pop_back());
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+        self.head = 0;
+    }
+
+    // Raw (front, back) halves of the logical range [start, end)
+    fn slice_ranges(&self, start: usize, end: usize) -> (*mut [T], *mut [T]) {
+        let len = end - start;
+        let first = self.to_physical(start);
+        let first_len = len.min(self.capacity() - first);
+        unsafe {
+            (
+                ptr::slice_from_raw_parts_mut(self.ptr().add(first), first_len),
+                ptr::slice_from_raw_parts_mut(self.ptr(), len - first_len),
+            )
+        }
+    }
+
+    /// The elements in order, as the part from `head` to the end of the
+    /// buffer and the part that wrapped around to the start. The second
+    /// slice is empty when the deque is contiguous.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (front, back) = self.slice_ranges(0, self.len);
+        unsafe { (&*front, &*back) }
+    }
+
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (front, back) = self.slice_ranges(0, self.len);
+        unsafe { (&mut *front, &mut *back) }
+    }
+
+    /// Rearranges the elements so they occupy one slice, without
+    /// reallocating, and returns it.
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if std::mem::size_of::<T>() == 0 {
+            self.head = 0;
+        }
+        if self.is_contiguous() {
+            return self.as_mut_slices().0;
+        }
+
+        // [tail . . . head_part]: `head_part` runs from head to the end of
+        // the buffer, `tail` wrapped around to index 0
+        let cap = self.capacity();
+        let head_len = cap - self.head;
+        let tail_len = self.len - head_len;
+        let free = cap - self.len;
+        let ptr = self.ptr();
+        unsafe {
+            if free >= head_len {
+                // [head_part tail . .]: shift the tail up, then drop the
+                // head part in front of it
+                ptr::copy(ptr, ptr.add(head_len), tail_len);
+                ptr::copy_nonoverlapping(ptr.add(self.head), ptr, head_len);
+                self.head = 0;
+            } else if free >= tail_len {
+                // [tail head_part tail]: slide the head part down next to
+                // the tail, then copy the tail after it
+                ptr::copy(ptr.add(self.head), ptr.add(tail_len), head_len);
+                ptr::copy_nonoverlapping(ptr, ptr.add(self.len), tail_len);
+                self.head = tail_len;
+            } else {
+                // Not enough room for either copy: rotate the whole buffer,
+                // free slots included, so the head part comes first
+                let all = slice::from_raw_parts_mut(ptr as *mut std::mem::MaybeUninit<T>, cap);
+                all.rotate_left(self.head);
+                self.head = 0;
+            }
+        }
+        self.as_mut_slices().0
+    }
+
+    /// Rotates the deque `n` places to the left, so the element at `n`
+    /// becomes the front. Moves `min(n, len - n)` elements.
+    pub fn rotate_left(&mut self, n: usize) {
+        assert!(n <= self.len, "rotation (is {}) should be <= len (is {})", n, self.len);
+        if n <= self.len / 2 {
+            self.rotate_left_inner(n);
+        } else {
+            self.rotate_right_inner(self.len - n);
+        }
+    }
+
+    /// Rotates the deque `n` places to the right, so the element at
+    /// `len - n` becomes the front. Moves `min(n, len - n)` elements.
+    pub fn rotate_right(&mut self, n: usize) {
+        assert!(n <= self.len, "rotation (is {}) should be <= len (is {})", n, self.len);
+        if n <= self.len / 2 {
+            self.rotate_right_inner(n);
+        } else {
+            self.rotate_left_inner(self.len - n);
+        }
+    }
+
+    // Moves the front `n` elements to the slots just past the back. Counted
+    // from the new head they sit at `cap - n..cap` and move down to
+    // `len - n..len`, so the copy runs forward even when the ranges overlap.
+    fn rotate_left_inner(&mut self, n: usize) {
+        self.head = self.wrap_add(self.head, n);
+        unsafe { self.wrap_copy(self.capacity() - n, self.len - n, n) };
+    }
+
+    // Moves the back `n` elements up to the slots just before the front,
+    // `cap - n..cap` counted from the old head, then steps the head back.
+    fn rotate_right_inner(&mut self, n: usize) {
+        unsafe { self.wrap_copy(self.len - n, self.capacity() - n, n) };
+        self.head = self.wrap_sub(self.head, n);
+    }
+
+    // Copies `count` elements from logical index `src` to `dst`. The ranges
+    // may overlap but must stay below the capacity; each contiguous run is
+    // one `ptr::copy`, in the direction that doesn't clobber the source.
+    unsafe fn wrap_copy(&mut self, src: usize, dst: usize, count: usize) {
+        if src == dst {
+            return;
+        }
+        let cap = self.capacity();
+        let ptr = self.ptr();
+        if dst <= src {
+            let mut done = 0;
+            while done < count {
+                let (from, to) = (self.to_physical(src + done), self.to_physical(dst + done));
+                let run = (count - done).min(cap - from).min(cap - to);
+                ptr::copy(ptr.add(from), ptr.add(to), run);
+                done += run;
+            }
+        } else {
+            let mut left = count;
+            while left > 0 {
+                let from_end = self.to_physical(src + left - 1) + 1;
+                let to_end = self.to_physical(dst + left - 1) + 1;
+                let run = left.min(from_end).min(to_end);
+                ptr::copy(ptr.add(from_end - run), ptr.add(to_end - run), run);
+                left -= run;
+            }
+        }
+    }
+
+    /// Removes the elements in `range` and returns them as an iterator. The
+    /// remaining elements are closed up when the iterator is dropped, moving
+    /// whichever side of the gap is shorter.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, A, G> {
+        let range = slice_range(range, self.len);
+        let orig_len = self.len;
+
+        // Until the Drain is dropped the deque only owns the front part, so
+        // leaking the Drain leaks elements but never double-drops them
+        self.len = range.start;
+        Drain {
+            deque: self,
+            start: range.start,
+            idx: range.start,
+            end: range.end,
+            drain_len: range.end - range.start,
+            orig_len,
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
+        Iter::new(front, back)
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (front, back) = self.as_mut_slices();
+        IterMut::new(front, back)
+    }
+}
+
+// Drops a slice when it goes out of scope, so the second half of the ring
+// is still dropped if a destructor in the first half panics
+pub(crate) struct DropSlice<T>(pub(crate) *mut [T]);
+
+impl<T> Drop for DropSlice<T> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.0) };
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Drop for VecDeque<T, A, G> {
+    fn drop(&mut self) {
+        let (front, back) = self.slice_ranges(0, self.len);
+        let _back = DropSlice(back);
+        unsafe { ptr::drop_in_place(front) };
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> From<Vec<T, A, G>> for VecDeque<T, A, G> {
+    /// Adopts the vector's buffer as-is. O(1).
+    fn from(vec: Vec<T, A, G>) -> Self {
+        let vec = ManuallyDrop::new(vec);
+        VecDeque { buf: unsafe { ptr::read(&vec.buf) }, head: 0, len: vec.len }
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> From<VecDeque<T, A, G>> for Vec<T, A, G> {
+    /// Hands the buffer to a `Vec` without reallocating. O(1) when the ring
+    /// already starts at the front of the buffer; otherwise the elements are
+    /// moved into place first.
+    fn from(mut deque: VecDeque<T, A, G>) -> Self {
+        deque.make_contiguous();
+        if deque.head != 0 {
+            unsafe { ptr::copy(deque.ptr().add(deque.head), deque.ptr(), deque.len) };
+        }
+        let deque = ManuallyDrop::new(deque);
+        Vec { buf: unsafe { ptr::read(&deque.buf) }, len: deque.len }
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Index<usize> for VecDeque<T, A, G> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(item) => item,
+            None => panic!("index (is {}) should be < len (is {})", index, self.len),
+        }
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> IndexMut<usize> for VecDeque<T, A, G> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        match self.get_mut(index) {
+            Some(item) => item,
+            None => panic!("index (is {}) should be < len (is {})", index, len),
+        }
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone, G: GrowthPolicy + Clone> Clone for VecDeque<T, A, G> {
+    fn clone(&self) -> Self {
+        let mut deque = VecDeque::with_policy_in(self.buf.policy().clone(), self.allocator().clone());
+        deque.extend(self.iter().cloned());
+        deque
+    }
+}
+
+impl<T: fmt::Debug, A: Allocator, G: GrowthPolicy> fmt::Debug for VecDeque<T, A, G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, A: Allocator, G: GrowthPolicy> PartialEq for VecDeque<T, A, G> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, A: Allocator, G: GrowthPolicy> Eq for VecDeque<T, A, G> {}
+
+impl<T> Default for VecDeque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Extend<T> for VecDeque<T, A, G> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for VecDeque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut deque = VecDeque::new();
+        deque.extend(iter);
+        deque
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> IntoIterator for VecDeque<T, A, G> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A, G>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { inner: self }
+    }
+}
+
+impl<'a, T, A: Allocator, G: GrowthPolicy> IntoIterator for &'a VecDeque<T, A, G> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, A: Allocator, G: GrowthPolicy> IntoIterator for &'a mut VecDeque<T, A, G> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Iterator over two slices in order: a `VecDeque`'s ring halves, or the
+/// elements on either side of a `GapBuffer`'s gap.
+pub struct Iter<'a, T> {
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    pub(crate) fn new(front: &'a [T], back: &'a [T]) -> Self {
+        Iter { front: front.iter(), back: back.iter() }
+    }
+}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter { front: self.front.clone(), back: self.back.clone() }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// Mutable counterpart of `Iter`.
+pub struct IterMut<'a, T> {
+    front: slice::IterMut<'a, T>,
+    back: slice::IterMut<'a, T>,
+}
+
+impl<'a, T> IterMut<'a, T> {
+    pub(crate) fn new(front: &'a mut [T], back: &'a mut [T]) -> Self {
+        IterMut { front: front.iter_mut(), back: back.iter_mut() }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+/// Consuming iterator; pops from either end of the owned deque.
+pub struct IntoIter<T, A: Allocator = Global, G: GrowthPolicy = TypeSizeAware> {
+    inner: VecDeque<T, A, G>,
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Iterator for IntoIter<T, A, G> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.inner.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.inner.len, Some(self.inner.len))
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> DoubleEndedIterator for IntoIter<T, A, G> {
+    fn next_back(&mut self) -> Option<T> {
+        self.inner.pop_back()
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> ExactSizeIterator for IntoIter<T, A, G> {}
+impl<T, A: Allocator, G: GrowthPolicy> FusedIterator for IntoIter<T, A, G> {}
+
+/// Draining iterator for `VecDeque::drain`.
+pub struct Drain<'a, T, A: Allocator = Global, G: GrowthPolicy = TypeSizeAware> {
+    deque: &'a mut VecDeque<T, A, G>,
+    start: usize,     // Logical start of the drained range
+    idx: usize,       // Next element to yield from the front
+    end: usize,       // One past the next element to yield from the back
+    drain_len: usize, // Size of the gap left behind
+    orig_len: usize,  // Deque length before draining
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Drain<'_, T, A, G> {
+    // Joins the elements before and after the drained range, moving the
+    // shorter side
+    fn close_gap(&mut self) {
+        let deque = &mut *self.deque;
+        let tail_start = self.start + self.drain_len;
+        let tail_len = self.orig_len - tail_start;
+        unsafe {
+            if self.start <= tail_len {
+                deque.wrap_copy(0, self.drain_len, self.start);
+                deque.head = deque.wrap_add(deque.head, self.drain_len);
+            } else {
+                deque.wrap_copy(tail_start, self.start, tail_len);
+            }
+        }
+        deque.len = self.orig_len - self.drain_len;
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> Iterator for Drain<'_, T, A, G> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.idx == self.end {
+            return None;
+        }
+        let item = self.deque.buf.read_at(self.deque.to_physical(self.idx));
+        self.idx += 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.idx;
+        (len, Some(len))
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> DoubleEndedIterator for Drain<'_, T, A, G> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.idx == self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(self.deque.buf.read_at(self.deque.to_physical(self.end)))
+    }
+}
+
+impl<T, A: Allocator, G: GrowthPolicy> ExactSizeIterator for Drain<'_, T, A, G> {}
+impl<T, A: Allocator, G: GrowthPolicy> FusedIterator for Drain<'_, T, A, G> {}
+
+impl<T, A: Allocator, G: GrowthPolicy> Drop for Drain<'_, T, A, G> {
+    fn drop(&mut self) {
+        // Closes the gap even if dropping an unyielded element panics
+        struct CloseGapOnDrop<'r, 'a, T, A: Allocator, G: GrowthPolicy>(&'r mut Drain<'a, T, A, G>);
+
+        impl<T, A: Allocator, G: GrowthPolicy> Drop for CloseGapOnDrop<'_, '_, T, A, G> {
+            fn drop(&mut self) {
+                self.0.close_gap();
+            }
+        }
+
+        let (front, back) = self.deque.slice_ranges(self.idx, self.end);
+        self.idx = self.end;
+        let _guard = CloseGapOnDrop(self);
+        let _back = DropSlice(back);
+        unsafe { ptr::drop_in_place(front) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Doubling, Exact};
+
+    // Capacity 8 with the head three slots in, so anything past five
+    // elements wraps around to the start of the buffer
+    fn wrapped<G: GrowthPolicy>(policy: G, len: usize) -> VecDeque<i32, Global, G> {
+        let mut deque = VecDeque::with_policy_in(policy, Global);
+        deque.reserve(8);
+        for _ in 0..3 {
+            deque.push_back(-1);
+            deque.pop_front();
+        }
+        deque.extend(0..len as i32);
+        assert_eq!(deque.capacity(), 8);
+        deque
+    }
+
+    fn collect<A: Allocator, G: GrowthPolicy>(deque: &VecDeque<i32, A, G>) -> std::vec::Vec<i32> {
+        deque.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_push_pop_both_ends() {
+        let mut deque = VecDeque::new();
+        deque.push_back(2);
+        deque.push_back(3);
+        deque.push_front(1);
+        deque.push_front(0);
+        assert_eq!(collect(&deque), [0, 1, 2, 3]);
+        assert_eq!(deque.front(), Some(&0));
+        assert_eq!(deque.back(), Some(&3));
+
+        assert_eq!(deque.pop_front(), Some(0));
+        assert_eq!(deque.pop_back(), Some(3));
+        assert_eq!(deque.pop_back(), Some(2));
+        assert_eq!(deque.pop_front(), Some(1));
+        assert_eq!(deque.pop_front(), None);
+        assert_eq!(deque.pop_back(), None);
+    }
+
+    #[test]
+    fn test_wraparound_and_slices() {
+        let deque = wrapped(Exact, 7);
+        assert_eq!(deque.capacity(), 8);
+        let (front, back) = deque.as_slices();
+        assert_eq!(front, &[0, 1, 2, 3, 4]);
+        assert_eq!(back, &[5, 6]);
+        assert_eq!(deque[5], 5);
+        assert_eq!(deque.iter().rev().copied().collect::<std::vec::Vec<_>>(), [6, 5, 4, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn test_growth_unwraps_ring() {
+        // Doubling leaves room to copy the short tail past the old end
+        let mut deque = wrapped(Doubling, 8);
+        deque.push_back(8);
+        assert_eq!(deque.capacity(), 16);
+        assert_eq!(deque.as_slices(), (&[0, 1, 2, 3, 4, 5, 6, 7, 8][..], &[][..]));
+
+        // One extra slot doesn't fit the tail, so the head moves to the end
+        let mut deque = wrapped(Exact, 8);
+        deque.push_back(8);
+        assert_eq!(deque.capacity(), 9);
+        assert_eq!(deque.as_slices(), (&[0, 1, 2, 3, 4][..], &[5, 6, 7, 8][..]));
+    }
+
+    #[test]
+    fn test_make_contiguous_every_layout() {
+        for len in 0..=8 {
+            for shift in 0..8 {
+                let mut deque = VecDeque::with_policy_in(Exact, Global);
+                deque.reserve(8);
+                for _ in 0..shift {
+                    deque.push_back(-1);
+                    deque.pop_front();
+                }
+                deque.extend(0..len);
+                let expected: std::vec::Vec<i32> = (0..len).collect();
+                assert_eq!(deque.make_contiguous(), &expected[..]);
+                assert_eq!(deque.as_slices().0, &expected[..]);
+                assert_eq!(deque.capacity(), 8);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotate() {
+        let mut deque: VecDeque<i32> = (0..10).collect();
+        deque.rotate_left(3);
+        assert_eq!(collect(&deque), [3, 4, 5, 6, 7, 8, 9, 0, 1, 2]);
+        deque.rotate_right(3);
+        assert_eq!(collect(&deque), (0..10).collect::<std::vec::Vec<_>>());
+        deque.rotate_left(8);
+        assert_eq!(collect(&deque), [8, 9, 0, 1, 2, 3, 4, 5, 6, 7]);
+
+        // Full buffer: rotation only moves the head
+        let mut deque = wrapped(Exact, 8);
+        deque.rotate_right(2);
+        assert_eq!(collect(&deque), [6, 7, 0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_rotate_every_layout() {
+        // Covers rotations that wrap and ones where the moved run overlaps
+        // its target because only a few slots are free
+        for len in 0..=8 {
+            for shift in 0..8 {
+                for n in 0..=len {
+                    let mut deque = VecDeque::with_policy_in(Exact, Global);
+                    deque.reserve(8);
+                    for _ in 0..shift {
+                        deque.push_back(-1);
+                        deque.pop_front();
+                    }
+                    deque.extend(0..len as i32);
+
+                    let mut expected: std::vec::Vec<i32> = (0..len as i32).collect();
+                    deque.rotate_left(n);
+                    expected.rotate_left(n);
+                    assert_eq!(collect(&deque), expected);
+                    deque.rotate_right(n);
+                    expected.rotate_right(n);
+                    assert_eq!(collect(&deque), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "rotation (is 4) should be <= len (is 3)")]
+    fn test_rotate_too_far() {
+        let mut deque: VecDeque<i32> = (0..3).collect();
+        deque.rotate_left(4);
+    }
+
+    #[test]
+    fn test_drain_closes_shorter_side() {
+        // Gap near the front: the front moves
+        let mut deque = wrapped(Exact, 8);
+        assert_eq!(deque.drain(1..3).collect::<std::vec::Vec<_>>(), [1, 2]);
+        assert_eq!(collect(&deque), [0, 3, 4, 5, 6, 7]);
+
+        // Gap near the back: the back moves, across the wrap
+        let mut deque = wrapped(Exact, 8);
+        let mut drain = deque.drain(4..7);
+        assert_eq!(drain.next_back(), Some(6));
+        drop(drain);
+        assert_eq!(collect(&deque), [0, 1, 2, 3, 7]);
+
+        let mut deque = wrapped(Exact, 5);
+        deque.drain(..);
+        assert!(deque.is_empty());
+    }
+
+    #[test]
+    fn test_vec_conversions() {
+        let vec: Vec<i32> = (0..5).collect();
+        let ptr = vec.as_ptr();
+        let mut deque = VecDeque::from(vec);
+        assert_eq!(deque.as_slices().0.as_ptr(), ptr);
+
+        deque.push_front(-1);
+        deque.pop_back();
+        let vec = Vec::from(deque);
+        assert_eq!(vec.as_ptr(), ptr);
+        assert_eq!(vec, [-1, 0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_zero_sized() {
+        let mut deque = VecDeque::new();
+        for _ in 0..10 {
+            deque.push_front(());
+            deque.push_back(());
+        }
+        assert_eq!(deque.len(), 20);
+        deque.rotate_left(5);
+        assert_eq!(deque.drain(2..6).count(), 4);
+        assert_eq!(deque.make_contiguous().len(), 16);
+        assert_eq!(deque.into_iter().count(), 16);
+    }
+
+    #[test]
+    #[should_panic(expected = "index (is 3) should be < len (is 3)")]
+    fn test_index_out_of_bounds() {
+        let deque: VecDeque<i32> = (0..3).collect();
+        let _ = deque[3];
+    }
+}
@@ -0,0 +1,252 @@
+use custom_vector_objones25::{AllocError, Allocator, Global, TryReserveError, Vec};
+use std::alloc::Layout;
+use std::cell::Cell;
+use std::ptr::NonNull;
+
+// Tracks every call so tests can check that each path goes through the allocator
+#[derive(Default)]
+struct Tracking {
+    allocs: Cell<usize>,
+    deallocs: Cell<usize>,
+    grows: Cell<usize>,
+    shrinks: Cell<usize>,
+    live_bytes: Cell<usize>,
+}
+
+unsafe impl Allocator for Tracking {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        self.allocs.set(self.allocs.get() + 1);
+        self.live_bytes.set(self.live_bytes.get() + layout.size());
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.deallocs.set(self.deallocs.get() + 1);
+        self.live_bytes.set(self.live_bytes.get() - layout.size());
+        Global.deallocate(ptr, layout)
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        self.grows.set(self.grows.get() + 1);
+        self.live_bytes.set(self.live_bytes.get() - old_layout.size() + new_layout.size());
+        Global.grow(ptr, old_layout, new_layout)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        self.shrinks.set(self.shrinks.get() + 1);
+        self.live_bytes.set(self.live_bytes.get() - old_layout.size() + new_layout.size());
+        Global.shrink(ptr, old_layout, new_layout)
+    }
+}
+
+#[test]
+fn test_push_grow_and_drop() {
+    let alloc = Tracking::default();
+    {
+        let mut vec = Vec::new_in(&alloc);
+        for i in 0..100 {
+            vec.push(i);
+        }
+        assert_eq!(vec.len(), 100);
+        assert_eq!(alloc.allocs.get(), 1);
+        assert!(alloc.grows.get() > 0);
+        assert!(alloc.live_bytes.get() >= 100 * std::mem::size_of::<i32>());
+    }
+    assert_eq!(alloc.deallocs.get(), 1);
+    assert_eq!(alloc.live_bytes.get(), 0);
+}
+
+#[test]
+fn test_with_capacity_in() {
+    let alloc = Tracking::default();
+    let vec: Vec<u64, _> = Vec::with_capacity_in(16, &alloc);
+    assert_eq!(vec.capacity(), 16);
+    assert_eq!(alloc.live_bytes.get(), 16 * 8);
+    drop(vec);
+    assert_eq!(alloc.live_bytes.get(), 0);
+}
+
+#[test]
+fn test_shrink_uses_allocator() {
+    let alloc = Tracking::default();
+    let mut vec = Vec::with_capacity_in(1024, &alloc);
+    for i in 0..10 {
+        vec.push(i);
+    }
+    vec.shrink_to_fit();
+    assert_eq!(alloc.shrinks.get(), 1);
+    assert_eq!(alloc.live_bytes.get(), vec.capacity() * std::mem::size_of::<i32>());
+    for i in 0..10 {
+        assert_eq!(vec[i], i as i32);
+    }
+}
+
+#[test]
+fn test_clone_carries_allocator() {
+    let alloc = Tracking::default();
+    let mut vec = Vec::new_in(&alloc);
+    vec.extend(0..10);
+    let cloned = vec.clone();
+    assert_eq!(alloc.allocs.get(), 2);
+    assert!(std::ptr::eq(*cloned.allocator(), &alloc));
+    assert_eq!(cloned.as_slice(), vec.as_slice());
+    drop(cloned);
+    drop(vec);
+    assert_eq!(alloc.live_bytes.get(), 0);
+}
+
+#[test]
+fn test_into_iter_carries_allocator() {
+    let alloc = Tracking::default();
+    let mut vec = Vec::new_in(&alloc);
+    vec.extend(0..10);
+    let mut iter = vec.into_iter();
+    assert!(std::ptr::eq(*iter.allocator(), &alloc));
+    assert_eq!(iter.next(), Some(0));
+    assert_eq!(alloc.deallocs.get(), 0);
+    drop(iter);
+    assert_eq!(alloc.deallocs.get(), 1);
+    assert_eq!(alloc.live_bytes.get(), 0);
+}
+
+#[test]
+fn test_map_collect_in_place() {
+    let alloc = Tracking::default();
+    {
+        let mut vec = Vec::new_in(&alloc);
+        vec.extend(0u32..50);
+        let allocs = alloc.allocs.get() + alloc.grows.get();
+
+        let vec = vec.into_iter().filter_map_collect(|x| (x % 5 == 0).then_some(x as f32));
+        assert_eq!(vec.len(), 10);
+        assert_eq!(alloc.allocs.get() + alloc.grows.get(), allocs);
+    }
+    assert_eq!(alloc.live_bytes.get(), 0);
+}
+
+#[test]
+fn test_drain_with_custom_allocator() {
+    let alloc = Tracking::default();
+    let mut vec = Vec::new_in(&alloc);
+    vec.extend(0..10);
+    let drained: std::vec::Vec<i32> = vec.drain(2..5).collect();
+    assert_eq!(drained, [2, 3, 4]);
+    assert_eq!(vec.as_slice(), &[0, 1, 5, 6, 7, 8, 9]);
+    assert_eq!(alloc.allocs.get(), 1);
+}
+
+// Refuses any allocation above `limit` bytes
+struct Limited {
+    limit: usize,
+}
+
+unsafe impl Allocator for Limited {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if layout.size() > self.limit {
+            return Err(AllocError);
+        }
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn test_splice_exact_hint_grows_once() {
+    let alloc = Tracking::default();
+    let mut vec = Vec::new_in(&alloc);
+    vec.extend(0..8);
+    let grows_before = alloc.grows.get();
+
+    // The exact size_hint lets splice make room for all 20 items in one step
+    vec.splice(2..3, 100..120);
+    assert_eq!(vec.len(), 27);
+    assert_eq!(alloc.grows.get(), grows_before + 1);
+    assert_eq!(&vec[..4], &[0, 1, 100, 101]);
+    assert_eq!(&vec[21..], &[119, 3, 4, 5, 6, 7]);
+}
+
+#[test]
+fn test_range_indexing_with_custom_allocator() {
+    let alloc = Tracking::default();
+    let mut vec = Vec::new_in(&alloc);
+    vec.extend(0..8);
+
+    assert_eq!(&vec[..2], &[0, 1]);
+    assert_eq!(&vec[6..], &[6, 7]);
+    vec[2..=3].fill(0);
+    assert_eq!(&vec[1..5], &[1, 0, 0, 4]);
+}
+
+#[test]
+fn test_bulk_moves_grow_once() {
+    let alloc = Tracking::default();
+    let mut vec = Vec::new_in(&alloc);
+    vec.push(0u32);
+    let mut other = Vec::new_in(&alloc);
+    other.extend(1..100u32);
+
+    let grows_before = alloc.grows.get();
+    vec.append(&mut other);
+    assert_eq!(alloc.grows.get(), grows_before + 1);
+
+    let grows_before = alloc.grows.get();
+    vec.extend_from_within(..);
+    assert!(alloc.grows.get() <= grows_before + 1);
+    assert_eq!(vec.len(), 200);
+    assert_eq!(vec[150], 50);
+}
+
+#[test]
+fn test_try_with_capacity_reports_alloc_error() {
+    let result = Vec::<u8, _>::try_with_capacity_in(1024, Limited { limit: 64 });
+    match result {
+        Err(TryReserveError::AllocError { layout }) => assert_eq!(layout.size(), 1024),
+        _ => panic!("expected allocation failure"),
+    }
+}
+
+#[test]
+fn test_try_push_sheds_load() {
+    let mut vec = Vec::new_in(Limited { limit: 64 });
+    let mut pushed = 0;
+    while vec.try_push(pushed as u8).is_ok() {
+        pushed += 1;
+    }
+    assert_eq!(pushed, 64);
+    assert_eq!(vec.len(), 64);
+    assert!(matches!(vec.try_push(0), Err(TryReserveError::AllocError { .. })));
+    // The vector is still usable after the failure
+    assert_eq!(vec.pop(), Some(63));
+    assert!(vec.try_push(63).is_ok());
+}
+
+#[test]
+fn test_try_extend_keeps_prefix_on_failure() {
+    let mut vec = Vec::new_in(Limited { limit: 32 });
+    let result = vec.try_extend((0..100u8).filter(|_| true));
+    assert!(result.is_err());
+    assert_eq!(vec.len(), 32);
+    assert_eq!(vec[31], 31);
+}
+
+#[test]
+fn test_try_reserve_reports_alloc_error() {
+    let mut vec: Vec<u32, _> = Vec::new_in(Limited { limit: 64 });
+    vec.push(1);
+    assert!(matches!(vec.try_reserve(100), Err(TryReserveError::AllocError { .. })));
+    assert_eq!(vec.len(), 1);
+    assert_eq!(vec[0], 1);
+}
@@ -0,0 +1,292 @@
+use custom_vector_objones25::{AllocError, Allocator, Global, Vec, VecDeque};
+use std::alloc::Layout;
+use std::cell::Cell;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+// Counts destructor runs; an element can be told to panic when dropped
+struct Tracked {
+    drops: Rc<Cell<usize>>,
+    panic_on_drop: bool,
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+        if self.panic_on_drop {
+            panic!("drop panicked");
+        }
+    }
+}
+
+fn tracked_vec(n: usize, panic_at: Option<usize>) -> (Vec<Tracked>, Rc<Cell<usize>>) {
+    let drops = Rc::new(Cell::new(0));
+    let mut vec = Vec::new();
+    for i in 0..n {
+        vec.push(Tracked { drops: drops.clone(), panic_on_drop: panic_at == Some(i) });
+    }
+    (vec, drops)
+}
+
+// Counts destructor runs; cloning panics once the shared budget runs out
+struct CloneBomb {
+    drops: Rc<Cell<usize>>,
+    clones_left: Rc<Cell<usize>>,
+}
+
+impl Clone for CloneBomb {
+    fn clone(&self) -> Self {
+        if self.clones_left.get() == 0 {
+            panic!("clone panicked");
+        }
+        self.clones_left.set(self.clones_left.get() - 1);
+        CloneBomb { drops: self.drops.clone(), clones_left: self.clones_left.clone() }
+    }
+}
+
+impl Drop for CloneBomb {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+fn bombs(n: usize, drops: &Rc<Cell<usize>>, clones_left: &Rc<Cell<usize>>) -> Vec<CloneBomb> {
+    let mut vec = Vec::new();
+    for _ in 0..n {
+        vec.push(CloneBomb { drops: drops.clone(), clones_left: clones_left.clone() });
+    }
+    vec
+}
+
+// Hands out memory like Global but panics instead of growing a block
+struct PanicOnGrow;
+
+unsafe impl Allocator for PanicOnGrow {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        Global.deallocate(ptr, layout)
+    }
+
+    unsafe fn grow(&self, _: NonNull<u8>, _: Layout, _: Layout) -> Result<NonNull<u8>, AllocError> {
+        panic!("grow panicked");
+    }
+}
+
+#[test]
+fn test_drop_vec_runs_every_destructor() {
+    let (vec, drops) = tracked_vec(10, None);
+    drop(vec);
+    assert_eq!(drops.get(), 10);
+}
+
+#[test]
+fn test_pop_insert_remove() {
+    let (mut vec, drops) = tracked_vec(5, None);
+
+    drop(vec.pop());
+    assert_eq!(drops.get(), 1);
+
+    vec.insert(1, Tracked { drops: drops.clone(), panic_on_drop: false });
+    drop(vec.remove(0));
+    assert_eq!(drops.get(), 2);
+    assert_eq!(vec.len(), 4);
+
+    drop(vec);
+    assert_eq!(drops.get(), 6);
+}
+
+#[test]
+fn test_truncate_and_clear() {
+    let (mut vec, drops) = tracked_vec(8, None);
+    vec.truncate(5);
+    assert_eq!(drops.get(), 3);
+
+    vec.clear();
+    assert_eq!(drops.get(), 8);
+    assert!(vec.is_empty());
+
+    drop(vec);
+    assert_eq!(drops.get(), 8);
+}
+
+#[test]
+fn test_drain_drops_unconsumed() {
+    let (mut vec, drops) = tracked_vec(8, None);
+    {
+        let mut drain = vec.drain(2..6);
+        drop(drain.next());
+        assert_eq!(drops.get(), 1);
+    }
+    // The three unconsumed elements went with the Drain
+    assert_eq!(drops.get(), 4);
+    assert_eq!(vec.len(), 4);
+
+    drop(vec);
+    assert_eq!(drops.get(), 8);
+}
+
+#[test]
+fn test_into_iter_drops_remaining() {
+    let (vec, drops) = tracked_vec(6, None);
+    let mut iter = vec.into_iter();
+    drop(iter.next());
+    drop(iter.next_back());
+    assert_eq!(drops.get(), 2);
+
+    drop(iter);
+    assert_eq!(drops.get(), 6);
+}
+
+#[test]
+fn test_zero_sized_elements() {
+    thread_local!(static DROPS: Cell<usize> = const { Cell::new(0) });
+
+    struct Zst;
+    impl Drop for Zst {
+        fn drop(&mut self) {
+            DROPS.with(|d| d.set(d.get() + 1));
+        }
+    }
+
+    let mut vec = Vec::new();
+    for _ in 0..5 {
+        vec.push(Zst);
+    }
+    let mut iter = vec.into_iter();
+    drop(iter.next());
+    drop(iter);
+    assert_eq!(DROPS.with(Cell::get), 5);
+}
+
+#[test]
+fn test_panic_while_dropping_vec() {
+    let (vec, drops) = tracked_vec(6, Some(2));
+    let result = catch_unwind(AssertUnwindSafe(|| drop(vec)));
+    assert!(result.is_err());
+
+    // Elements after the panicking one are still dropped, each exactly once
+    assert_eq!(drops.get(), 6);
+}
+
+#[test]
+fn test_panic_during_truncate() {
+    let (mut vec, drops) = tracked_vec(6, Some(3));
+    let result = catch_unwind(AssertUnwindSafe(|| vec.truncate(2)));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 4);
+
+    // The length was updated before dropping, so nothing is dropped twice
+    assert_eq!(vec.len(), 2);
+    drop(vec);
+    assert_eq!(drops.get(), 6);
+}
+
+#[test]
+fn test_panic_dropping_removed_element() {
+    // remove itself finishes; the panic comes from dropping what it returned
+    let (mut vec, drops) = tracked_vec(4, Some(1));
+    let result = catch_unwind(AssertUnwindSafe(|| drop(vec.remove(1))));
+    assert!(result.is_err());
+    assert_eq!(vec.len(), 3);
+
+    drop(vec);
+    assert_eq!(drops.get(), 4);
+}
+
+#[test]
+fn test_panic_cloning_during_resize() {
+    let (drops, clones_left) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(2)));
+    let mut vec = bombs(2, &drops, &clones_left);
+    let value = CloneBomb { drops: drops.clone(), clones_left: clones_left.clone() };
+
+    // Two clones land, the third panics and the moved-in value is dropped
+    let result = catch_unwind(AssertUnwindSafe(|| vec.resize(6, value)));
+    assert!(result.is_err());
+    assert_eq!(vec.len(), 4);
+    assert_eq!(drops.get(), 1);
+
+    drop(vec);
+    assert_eq!(drops.get(), 5);
+}
+
+#[test]
+fn test_panic_cloning_during_extend_from_slice() {
+    let (drops, clones_left) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(1)));
+    let mut vec = bombs(2, &drops, &clones_left);
+    let source = bombs(3, &drops, &clones_left);
+
+    // Only the clone that finished is part of the vector
+    let result = catch_unwind(AssertUnwindSafe(|| vec.extend_from_slice(&source)));
+    assert!(result.is_err());
+    assert_eq!(vec.len(), 3);
+    assert_eq!(drops.get(), 0);
+
+    drop(vec);
+    drop(source);
+    assert_eq!(drops.get(), 6);
+}
+
+#[test]
+fn test_panic_growing_during_insert() {
+    let drops = Rc::new(Cell::new(0));
+    let mut vec = Vec::with_capacity_in(4, PanicOnGrow);
+    for _ in 0..4 {
+        vec.push(Tracked { drops: drops.clone(), panic_on_drop: false });
+    }
+    assert_eq!(vec.len(), vec.capacity());
+
+    // Nothing has been shifted yet when the allocator panics; only the
+    // element being inserted is dropped
+    let item = Tracked { drops: drops.clone(), panic_on_drop: false };
+    let result = catch_unwind(AssertUnwindSafe(|| vec.insert(1, item)));
+    assert!(result.is_err());
+    assert_eq!(vec.len(), 4);
+    assert_eq!(drops.get(), 1);
+
+    drop(vec);
+    assert_eq!(drops.get(), 5);
+}
+
+#[test]
+fn test_panic_while_dropping_drain() {
+    let (mut vec, drops) = tracked_vec(8, Some(3));
+    let result = catch_unwind(AssertUnwindSafe(|| drop(vec.drain(2..5))));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 3);
+
+    // The tail was still moved back into place
+    assert_eq!(vec.len(), 5);
+    drop(vec);
+    assert_eq!(drops.get(), 8);
+}
+
+#[test]
+fn test_panic_while_dropping_into_iter() {
+    let (vec, drops) = tracked_vec(6, Some(4));
+    let mut iter = vec.into_iter();
+    drop(iter.next());
+
+    let result = catch_unwind(AssertUnwindSafe(|| drop(iter)));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 6);
+}
+
+#[test]
+fn test_vec_deque_wrapped_ring() {
+    let (vec, drops) = tracked_vec(6, None);
+    let mut deque = VecDeque::from(vec);
+    deque.rotate_right(2);
+    deque.push_front(Tracked { drops: drops.clone(), panic_on_drop: false });
+
+    // The drained range straddles the wrap point
+    drop(deque.drain(1..4));
+    assert_eq!(drops.get(), 3);
+    assert_eq!(deque.len(), 4);
+
+    drop(deque);
+    assert_eq!(drops.get(), 7);
+}
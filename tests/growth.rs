@@ -0,0 +1,68 @@
+use custom_vector_objones25::{Doubling, Exact, Fibonacci, Global, GrowthPolicy, PageRounded, TypeSizeAware, Vec};
+
+// Counts how many times capacity changes while pushing `n` elements
+fn reallocations<G: GrowthPolicy>(policy: G, n: usize) -> (usize, usize) {
+    let mut vec: Vec<u64, Global, G> = Vec::with_policy(policy);
+    let mut count = 0;
+    let mut last_cap = vec.capacity();
+    for i in 0..n {
+        vec.push(i as u64);
+        if vec.capacity() != last_cap {
+            count += 1;
+            last_cap = vec.capacity();
+        }
+    }
+    (count, vec.capacity())
+}
+
+#[test]
+fn test_policies_trade_memory_for_reallocations() {
+    let n = 10_000;
+    let (doubling, doubling_cap) = reallocations(Doubling, n);
+    let (fibonacci, fibonacci_cap) = reallocations(Fibonacci, n);
+    let (exact, exact_cap) = reallocations(Exact, n);
+    let (type_size, _) = reallocations(TypeSizeAware, n);
+
+    assert_eq!(exact, n);
+    assert_eq!(exact_cap, n);
+    assert!(doubling < fibonacci);
+    assert!(fibonacci < exact);
+    assert!(type_size < fibonacci);
+    assert!(doubling_cap >= n && fibonacci_cap >= n);
+}
+
+#[test]
+fn test_page_rounded_fills_whole_pages() {
+    let policy = PageRounded::new(4096);
+    let mut vec: Vec<u64, Global, PageRounded> = Vec::with_policy(policy);
+    for i in 0..3000 {
+        vec.push(i);
+        assert_eq!(vec.capacity() * 8 % 4096, 0);
+    }
+    assert_eq!(vec.growth_policy().page_size(), 4096);
+}
+
+#[test]
+fn test_shrink_follows_policy() {
+    let mut vec: Vec<u64, Global, Exact> = Vec::with_policy(Exact);
+    vec.extend(0..100);
+    vec.truncate(90);
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 90);
+
+    let mut vec: Vec<u64> = Vec::new();
+    vec.extend(0..100);
+    vec.truncate(90);
+    vec.shrink_to_fit();
+    // The default policy keeps the buffer when it is mostly used
+    assert!(vec.capacity() >= 100);
+}
+
+#[test]
+fn test_clone_keeps_policy() {
+    let mut vec = Vec::with_policy(PageRounded::new(8192));
+    vec.push(1u8);
+    let cloned = vec.clone();
+    assert_eq!(cloned.growth_policy().page_size(), 8192);
+    assert_eq!(cloned[0], 1);
+}
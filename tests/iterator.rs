@@ -39,6 +39,7 @@ fn test_iter() {
 }
 
 #[test]
+#[allow(clippy::while_let_on_iterator)]
 fn test_iter_mut() {
     let mut vec = Vec::new();
     vec.extend(0..5);
@@ -88,6 +89,25 @@ fn test_drain() {
 }
 
 #[test]
+fn test_drain_keep_rest_and_as_slice() {
+    let mut vec = Vec::new();
+    vec.extend(0..8);
+
+    // Take the first two, put the rest of the range back
+    let mut drain = vec.drain(2..);
+    assert_eq!(drain.as_slice(), &[2, 3, 4, 5, 6, 7]);
+    assert_eq!(drain.next(), Some(2));
+    assert_eq!(drain.next(), Some(3));
+    drain.keep_rest();
+    assert_eq!(vec.as_slice(), &[0, 1, 4, 5, 6, 7]);
+
+    // Untouched drain keeps everything
+    vec.drain(1..3).keep_rest();
+    assert_eq!(vec.as_slice(), &[0, 1, 4, 5, 6, 7]);
+}
+
+#[test]
+#[allow(clippy::useless_vec)]
 fn test_iterator_collect() {
     let mut vec = Vec::new();
     vec.extend(0..5);
@@ -172,3 +192,63 @@ fn test_iterator_filter_map() {
     assert_eq!(squared[3], 1);  // 1^2
     assert_eq!(squared[4], 4);  // 2^2
 }
+
+#[test]
+fn test_exact_size_and_fused() {
+    let vec: Vec<i32> = (0..10).collect();
+
+    let mut iter = vec.iter();
+    assert_eq!(iter.len(), 10);
+    iter.nth(8);
+    assert_eq!(iter.len(), 1);
+    assert_eq!(iter.next(), Some(&9));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next(), None);
+
+    let mut drain_source = vec.clone();
+    let drain = drain_source.drain(2..7);
+    assert_eq!(drain.len(), 5);
+}
+
+#[test]
+fn test_rev_skip_uses_nth_back() {
+    let vec: Vec<i32> = (0..10).collect();
+    let skipped: Vec<i32> = vec.iter().rev().skip(7).copied().collect();
+    assert_eq!(skipped.as_slice(), &[2, 1, 0]);
+
+    let owned: Vec<i32> = vec.into_iter().rev().skip(8).collect();
+    assert_eq!(owned.as_slice(), &[1, 0]);
+}
+
+#[test]
+fn test_remaining_slices() {
+    let mut vec: Vec<i32> = (0..6).collect();
+
+    let mut iter = vec.iter_mut();
+    iter.next();
+    iter.next_back();
+    iter.as_mut_slice()[0] = 10;
+    assert_eq!(iter.as_slice(), &[10, 2, 3, 4]);
+
+    let mut drain = vec.drain(..);
+    assert_eq!(drain.nth(2), Some(2));
+    assert_eq!(drain.as_slice(), &[3, 4, 5]);
+    assert_eq!(drain.nth_back(1), Some(4));
+    assert_eq!(drain.as_slice(), &[3]);
+}
+
+#[test]
+fn test_skipped_owned_elements_are_dropped() {
+    use std::rc::Rc;
+
+    let marker = Rc::new(());
+    let vec: Vec<Rc<()>> = (0..5).map(|_| marker.clone()).collect();
+    let mut iter = vec.into_iter();
+
+    // nth drops the skipped elements right away
+    let third = iter.nth(2);
+    assert_eq!(Rc::strong_count(&marker), 4);
+    drop(third);
+    assert_eq!(iter.count(), 2);
+    assert_eq!(Rc::strong_count(&marker), 1);
+}
@@ -0,0 +1,67 @@
+use custom_vector_objones25::{ArrayVec, Vec};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+// Counts allocations made by the current thread, so tests running in
+// parallel don't see each other's
+struct Counting;
+
+thread_local! {
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCS.with(|n| n.set(n.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCS.with(Cell::get);
+    let result = f();
+    (result, ALLOCS.with(Cell::get) - before)
+}
+
+#[test]
+fn test_array_vec_never_allocates() {
+    let (sum, count) = allocations(|| {
+        let mut vec: ArrayVec<u64, 16> = ArrayVec::new();
+        for i in 0..16 {
+            vec.push(i).unwrap();
+        }
+        assert!(vec.push(16).is_err());
+        vec.pop();
+        vec.try_insert(3, 100).unwrap();
+        vec.remove(0);
+        vec.drain(2..5).for_each(drop);
+        vec.extend([7, 8]);
+        vec.into_iter().sum::<u64>()
+    });
+    assert_eq!(sum, 113);
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn test_array_vec_into_vec_allocates_only_when_needed() {
+    let (vec, count) = allocations(|| ArrayVec::<u8, 8>::new().into_vec());
+    assert_eq!((vec.capacity(), count), (0, 0));
+
+    // The counter does see the one allocation a non-empty conversion makes
+    let full: ArrayVec<u8, 8> = (0..8).collect();
+    let (vec, count) = allocations(|| full.into_vec());
+    assert_eq!(vec, Vec::from([0, 1, 2, 3, 4, 5, 6, 7]));
+    assert_eq!(count, 1);
+}
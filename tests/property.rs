@@ -1,4 +1,4 @@
-use custom_vector_objones25::Vec;
+use custom_vector_objones25::{GapBuffer, SmallVec, ThinVec, Vec, VecDeque};
 use proptest::prelude::*;
 
 proptest! {
@@ -55,10 +55,7 @@ proptest! {
             }
             
             // Verify length and elements
-            prop_assert_eq!(vec.len(), reference.len());
-            for i in 0..reference.len() {
-                prop_assert_eq!(vec[i], reference[i]);
-            }
+            prop_assert_eq!(&vec, &reference);
         }
     }
     
@@ -75,10 +72,7 @@ proptest! {
             reference.extend(chunk);
             
             // Verify length and elements
-            prop_assert_eq!(vec.len(), reference.len());
-            for i in 0..reference.len() {
-                prop_assert_eq!(vec[i], reference[i]);
-            }
+            prop_assert_eq!(&vec, &reference);
             
             // Clear periodically
             if reference.len() > 10 {
@@ -146,10 +140,171 @@ proptest! {
             prop_assert_eq!(drained.as_slice(), reference_drained.as_slice());
             
             // Verify remaining elements
-            prop_assert_eq!(vec.len(), reference.len());
-            for i in 0..reference.len() {
-                prop_assert_eq!(vec[i], reference[i]);
+            prop_assert_eq!(&vec, &reference);
+        }
+    }
+
+    #[test]
+    fn test_small_vec_matches_std(
+        operations in prop::collection::vec((0..3u8, 0..40usize, 0..100i32), 0..80)
+    ) {
+        let mut small: SmallVec<i32, 4> = SmallVec::new();
+        let mut reference = std::vec::Vec::new();
+
+        for (op, index, value) in operations {
+            match op {
+                0 => {
+                    small.push(value);
+                    reference.push(value);
+                }
+                1 if !reference.is_empty() => {
+                    let index = index % reference.len();
+                    prop_assert_eq!(small.remove(index), reference.remove(index));
+                }
+                _ => {
+                    let index = index % (reference.len() + 1);
+                    small.insert(index, value);
+                    reference.insert(index, value);
+                }
+            }
+
+            prop_assert_eq!(small.as_slice(), reference.as_slice());
+            prop_assert_eq!(small.spilled(), reference.len() > 4 || small.capacity() > 4);
+        }
+
+        let vec = small.into_vec();
+        prop_assert_eq!(&vec, &reference);
+    }
+
+    #[test]
+    fn test_retain_and_extract_if_match_std(
+        values in prop::collection::vec(0..100i32, 0..50),
+        divisor in 1..5i32,
+        range_start in 0usize..50
+    ) {
+        let mut vec: Vec<i32> = values.iter().copied().collect();
+        let mut reference = values.clone();
+
+        vec.retain(|x| x % divisor != 0);
+        reference.retain(|x| x % divisor != 0);
+        prop_assert_eq!(&vec, &reference);
+
+        let start = range_start % (reference.len() + 1);
+        let extracted: std::vec::Vec<_> = vec.extract_if(start.., |x| *x > 50).collect();
+        let reference_extracted: std::vec::Vec<_> =
+            reference.extract_if(start.., |x| *x > 50).collect();
+        prop_assert_eq!(extracted, reference_extracted);
+        prop_assert_eq!(&vec, &reference);
+    }
+
+    #[test]
+    fn test_dedup_matches_std(values in prop::collection::vec(0..5i32, 0..50)) {
+        let mut vec: Vec<i32> = values.iter().copied().collect();
+        let mut reference = values.clone();
+
+        vec.dedup();
+        reference.dedup();
+        prop_assert_eq!(&vec, &reference);
+    }
+
+    #[test]
+    fn test_vec_deque_matches_std(
+        operations in prop::collection::vec((0..7u8, 0..100i32, 0..50usize), 0..100)
+    ) {
+        let mut deque = VecDeque::new();
+        let mut reference = std::collections::VecDeque::new();
+
+        for (op, value, index) in operations {
+            match op {
+                0 => { deque.push_back(value); reference.push_back(value); }
+                1 => { deque.push_front(value); reference.push_front(value); }
+                2 => prop_assert_eq!(deque.pop_back(), reference.pop_back()),
+                3 => prop_assert_eq!(deque.pop_front(), reference.pop_front()),
+                4 => {
+                    let n = index % (reference.len() + 1);
+                    deque.rotate_left(n);
+                    reference.rotate_left(n);
+                }
+                5 => {
+                    let start = index % (reference.len() + 1);
+                    let end = start + value as usize % (reference.len() - start + 1);
+                    let drained: std::vec::Vec<_> = deque.drain(start..end).collect();
+                    let expected: std::vec::Vec<_> = reference.drain(start..end).collect();
+                    prop_assert_eq!(drained, expected);
+                }
+                _ => prop_assert_eq!(deque.make_contiguous(), reference.make_contiguous()),
+            }
+            prop_assert!(deque.iter().eq(reference.iter()));
+        }
+
+        let vec = Vec::from(deque);
+        prop_assert!(vec.iter().eq(reference.iter()));
+    }
+
+    #[test]
+    fn test_gap_buffer_matches_std(
+        operations in prop::collection::vec((0..4u8, 0..100i32, 0..50usize), 0..100)
+    ) {
+        let mut buffer = GapBuffer::new();
+        let mut reference = std::vec::Vec::new();
+
+        for (op, value, index) in operations {
+            match op {
+                0 | 1 => {
+                    let index = index % (reference.len() + 1);
+                    buffer.insert(index, value);
+                    reference.insert(index, value);
+                }
+                2 if !reference.is_empty() => {
+                    let index = index % reference.len();
+                    prop_assert_eq!(buffer.remove(index), reference.remove(index));
+                }
+                _ => buffer.move_gap_to(index % (reference.len() + 1)),
+            }
+            prop_assert!(buffer.iter().eq(reference.iter()));
+        }
+
+        prop_assert_eq!(buffer.make_contiguous(), &reference[..]);
+        prop_assert_eq!(&Vec::from(buffer), &reference);
+    }
+
+    #[test]
+    fn test_thin_vec_matches_std(
+        operations in prop::collection::vec((0..6u8, 0..100i32, 0..50usize), 0..100)
+    ) {
+        let mut vec = ThinVec::new();
+        let mut reference = std::vec::Vec::new();
+
+        for (op, value, index) in operations {
+            match op {
+                0 => { vec.push(value); reference.push(value); }
+                1 => prop_assert_eq!(vec.pop(), reference.pop()),
+                2 => {
+                    let index = index % (reference.len() + 1);
+                    vec.insert(index, value);
+                    reference.insert(index, value);
+                }
+                3 if !reference.is_empty() => {
+                    let index = index % reference.len();
+                    prop_assert_eq!(vec.remove(index), reference.remove(index));
+                }
+                4 => {
+                    let start = index % (reference.len() + 1);
+                    let end = start + value as usize % (reference.len() - start + 1);
+                    let replacement = 0..value % 4;
+                    let removed: std::vec::Vec<_> = vec.splice(start..end, replacement.clone()).collect();
+                    let expected: std::vec::Vec<_> = reference.splice(start..end, replacement).collect();
+                    prop_assert_eq!(removed, expected);
+                }
+                _ => {
+                    vec.truncate(index);
+                    reference.truncate(index);
+                    vec.shrink_to_fit();
+                }
             }
+            prop_assert_eq!(&vec[..], &reference[..]);
         }
+
+        prop_assert_eq!(&Vec::from(vec), &reference);
     }
 }